@@ -0,0 +1,153 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use itertools::Itertools;
+use syn::{ItemTrait, Path};
+use syn::spanned::Spanned;
+
+use crate::error::{Error, Result};
+use crate::ops::is_operator;
+
+// Traits from the standard library can't be annotated with `#[forwardable]`, so their
+// signatures are kept here instead.
+struct Builtin {
+    // the paths the trait can be named by, with any leading `std`, `core` or `alloc` removed
+    names: &'static [&'static str],
+    path: &'static str,
+    // a definition containing only the methods that should be forwarded
+    def: &'static str,
+    // the feature needed to forward the trait, and whether it is enabled
+    feature: Option<(&'static str, bool)>,
+}
+
+const BUILTINS: &[Builtin] = &[
+    Builtin {
+        names: &["Display", "fmt::Display"],
+        path: "::core::fmt::Display",
+        def: "trait Display { fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result; }",
+        feature: None,
+    },
+    Builtin {
+        names: &["Debug", "fmt::Debug"],
+        path: "::core::fmt::Debug",
+        def: "trait Debug { fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result; }",
+        feature: None,
+    },
+    Builtin {
+        names: &["fmt::Write"],
+        path: "::core::fmt::Write",
+        def: "trait Write {
+            fn write_str(&mut self, s: &str) -> ::core::fmt::Result;
+            fn write_char(&mut self, c: char) -> ::core::fmt::Result;
+            fn write_fmt(&mut self, args: ::core::fmt::Arguments<'_>) -> ::core::fmt::Result;
+        }",
+        feature: None,
+    },
+    Builtin {
+        names: &["AsRef", "convert::AsRef"],
+        path: "::core::convert::AsRef",
+        def: "trait AsRef<T: ?Sized> { fn as_ref(&self) -> &T; }",
+        feature: None,
+    },
+    Builtin {
+        names: &["AsMut", "convert::AsMut"],
+        path: "::core::convert::AsMut",
+        def: "trait AsMut<T: ?Sized> { fn as_mut(&mut self) -> &mut T; }",
+        feature: None,
+    },
+    Builtin {
+        names: &["Iterator", "iter::Iterator"],
+        path: "::core::iter::Iterator",
+        def: "trait Iterator {
+            type Item;
+            fn next(&mut self) -> ::core::option::Option<Self::Item>;
+            fn size_hint(&self) -> (usize, ::core::option::Option<usize>);
+        }",
+        feature: None,
+    },
+    Builtin {
+        names: &["DoubleEndedIterator", "iter::DoubleEndedIterator"],
+        path: "::core::iter::DoubleEndedIterator",
+        def: "trait DoubleEndedIterator {
+            fn next_back(&mut self) -> ::core::option::Option<Self::Item>;
+        }",
+        feature: None,
+    },
+    Builtin {
+        names: &["ExactSizeIterator", "iter::ExactSizeIterator"],
+        path: "::core::iter::ExactSizeIterator",
+        def: "trait ExactSizeIterator { fn len(&self) -> usize; }",
+        feature: None,
+    },
+    Builtin {
+        names: &["Index", "ops::Index"],
+        path: "::core::ops::Index",
+        def: "trait Index<Idx> {
+            type Output;
+            fn index(&self, index: Idx) -> &Self::Output;
+        }",
+        feature: None,
+    },
+    Builtin {
+        names: &["IndexMut", "ops::IndexMut"],
+        path: "::core::ops::IndexMut",
+        def: "trait IndexMut<Idx> { fn index_mut(&mut self, index: Idx) -> &mut Self::Output; }",
+        feature: None,
+    },
+    Builtin {
+        names: &["Error", "error::Error"],
+        path: "::core::error::Error",
+        def: "trait Error { fn source(&self) -> ::core::option::Option<&(dyn ::core::error::Error + 'static)>; }",
+        feature: None,
+    },
+    Builtin {
+        names: &["Read", "io::Read"],
+        path: "::std::io::Read",
+        def: "trait Read { fn read(&mut self, buf: &mut [u8]) -> ::std::io::Result<usize>; }",
+        feature: Some(("std", cfg!(feature = "std"))),
+    },
+    Builtin {
+        names: &["Write", "io::Write"],
+        path: "::std::io::Write",
+        def: "trait Write {
+            fn write(&mut self, buf: &[u8]) -> ::std::io::Result<usize>;
+            fn flush(&mut self) -> ::std::io::Result<()>;
+        }",
+        feature: Some(("std", cfg!(feature = "std"))),
+    },
+];
+
+/// Check if a path in `#[forward(...)]` is the bare name of a standard library trait, which a
+/// forwardable trait of the same name takes precedence over when it's in scope. `Debug` always
+/// names the standard library trait, as a macro of that name would shadow `#[derive(Debug)]`.
+pub(crate) fn shadowable(path: &Path) -> bool {
+    let Some(segment) = path.leading_colon.is_none().then(|| path.segments.iter().exactly_one().ok()).flatten() else {
+        return false;
+    };
+    let name = segment.ident.to_string();
+    name != "Debug" && (is_operator(path) || BUILTINS.iter().any(|b| b.names.contains(&name.as_str())))
+}
+
+/// Look up a standard library trait by the path it was named with in `#[forward(...)]`,
+/// returning its absolute path and forwardable definition.
+pub(crate) fn builtin_trait(path: &Path) -> Result<Option<(Path, ItemTrait)>> {
+    let mut segments = path.segments.iter().map(|s| s.ident.to_string()).collect::<Vec<_>>();
+    if segments.len() > 1 && ["std", "core", "alloc"].contains(&segments[0].as_str()) {
+        segments.remove(0);
+    }
+    let name = segments.join("::");
+
+    let Some(builtin) = BUILTINS.iter().find(|b| b.names.contains(&name.as_str())) else {
+        return Ok(None);
+    };
+
+    if let Some((feature, false)) = builtin.feature {
+        return Err(Error::Other(path.span(), format!(
+            "Forwarding `{}` requires the `{}` feature of enum-forward", name, feature)));
+    }
+
+    let builtin_path = syn::parse_str::<Path>(builtin.path).expect("invalid builtin trait path");
+    let def = syn::parse_str::<ItemTrait>(builtin.def).expect("invalid builtin trait definition");
+    Ok(Some((builtin_path, def)))
+}
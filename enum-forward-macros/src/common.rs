@@ -3,11 +3,17 @@
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
 use std::collections::HashSet;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use itertools::Itertools;
 use proc_macro2::{Group, Ident, TokenStream, TokenTree};
-use quote::quote;
-use syn::{Fields, ItemEnum, Lifetime, Type, TypeArray, TypeGroup, TypeParen, TypePtr, TypeReference, TypeSlice, TypeTuple, Variant};
+use quote::{format_ident, quote, ToTokens};
+use syn::{AngleBracketedGenericArguments, Attribute, Fields, File, GenericArgument, GenericParam, Generics, ImplItem, Item, ItemEnum, ItemStruct, Lifetime, Member, parse2, parse_quote, Path, PathArguments, Token, TraitItem, TraitItemFn, Type, TypeArray, TypeGroup, TypeParen, TypePtr, TypeReference, TypeSlice, TypeTuple, Variant, Visibility};
+use syn::meta::ParseNestedMeta;
+use syn::parse::{Parse, ParseStream};
 use syn::spanned::Spanned;
-use crate::error::{Error, Result};
+use syn::token::Paren;
+use syn::visit_mut::{self, VisitMut};
+use crate::error::{Error, Errors, Result};
 
 pub(crate) struct VariantInfo<'a> {
     pub variant: &'a Variant,
@@ -15,14 +21,14 @@ pub(crate) struct VariantInfo<'a> {
     pub pattern: TokenStream,
 }
 
-pub(crate) fn variant_patterns(item: &ItemEnum) -> impl Iterator<Item=Result<VariantInfo>> {
+pub(crate) fn variant_patterns(item: &ItemEnum) -> impl Iterator<Item=Result<VariantInfo<'_>>> {
     let item_ident = item.ident.clone();
 
     item.variants.iter().map(move |variant| {
         let var_ident = &variant.ident;
-        return match &variant.fields {
+        match &variant.fields {
             Fields::Named(ns) => {
-                if ns.named.len() < 1 {
+                if ns.named.is_empty() {
                     return Err(Error::UnitVariant(variant.span()));
                 }
                 if ns.named.len() > 1 {
@@ -34,7 +40,7 @@ pub(crate) fn variant_patterns(item: &ItemEnum) -> impl Iterator<Item=Result<Var
                 Ok(VariantInfo { variant, inner_ty, pattern })
             }
             Fields::Unnamed(us) => {
-                if us.unnamed.len() < 1 {
+                if us.unnamed.is_empty() {
                     return Err(Error::UnitVariant(variant.span()));
                 }
                 if us.unnamed.len() > 1 {
@@ -47,26 +53,558 @@ pub(crate) fn variant_patterns(item: &ItemEnum) -> impl Iterator<Item=Result<Var
             Fields::Unit => {
                 Err(Error::UnitVariant(variant.span()))
             }
-        };
+        }
     })
 }
 
-pub(crate) fn replace_ident(ts: TokenStream, from: Ident, to: Ident) -> TokenStream {
+pub(crate) struct FieldInfo<'a> {
+    pub inner_ty: &'a Type,
+    pub pattern: TokenStream,
+}
+
+/// Find the field a struct forwards to, which is either its only field or the one marked with `#[forward]`
+pub(crate) fn field_pattern(item: &ItemStruct) -> Result<FieldInfo<'_>> {
+    let item_ident = &item.ident;
+
+    let marked = item.fields.iter().enumerate().filter(
+        |(_, f)| f.attrs.iter().any(|a| a.path().is_ident("forward"))
+    ).collect::<Vec<_>>();
+
+    let (index, field) = match (marked.as_slice(), item.fields.len()) {
+        ([], 0) => {
+            return Err(Error::Other(item.span(), "Struct has no fields to forward to".into()));
+        }
+        ([], 1) => (0, item.fields.iter().next().unwrap()),
+        ([], _) => {
+            return Err(Error::AmbiguousField(item.fields.span()));
+        }
+        ([marked], _) => *marked,
+        ([_, second, ..], _) => {
+            return Err(Error::AmbiguousField(second.1.span()));
+        }
+    };
+
+    let member = match &field.ident {
+        Some(ident) => Member::Named(ident.clone()),
+        None => Member::Unnamed(index.into()),
+    };
+    let pattern = quote! { #item_ident{#member : value, ..} };
+    Ok(FieldInfo { inner_ty: &field.ty, pattern })
+}
+
+/// An item that can be forwarded: an enum forwarding to its variants, or a struct forwarding to one of its fields
+pub(crate) enum ForwardItem {
+    Enum(ItemEnum),
+    Struct(ItemStruct),
+}
+
+/// One arm of a match on a [ForwardItem], binding `value` to the inner value
+pub(crate) struct ForwardArm<'a> {
+    /// the variant matched, or `None` for a struct
+    pub variant: Option<&'a Variant>,
+    pub inner_ty: &'a Type,
+    pub pattern: TokenStream,
+}
+
+impl Parse for ForwardItem {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        match input.parse::<Item>()? {
+            Item::Enum(item) => Ok(ForwardItem::Enum(item)),
+            Item::Struct(item) => Ok(ForwardItem::Struct(item)),
+            other => Err(syn::Error::new(other.span(), "Only enums and structs can be forwarded")),
+        }
+    }
+}
+
+impl ToTokens for ForwardItem {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        match self {
+            ForwardItem::Enum(item) => item.to_tokens(tokens),
+            ForwardItem::Struct(item) => item.to_tokens(tokens),
+        }
+    }
+}
+
+impl ForwardItem {
+    pub fn ident(&self) -> &Ident {
+        match self {
+            ForwardItem::Enum(item) => &item.ident,
+            ForwardItem::Struct(item) => &item.ident,
+        }
+    }
+
+    pub fn attrs(&self) -> &[Attribute] {
+        match self {
+            ForwardItem::Enum(item) => &item.attrs,
+            ForwardItem::Struct(item) => &item.attrs,
+        }
+    }
+
+    pub fn vis(&self) -> &Visibility {
+        match self {
+            ForwardItem::Enum(item) => &item.vis,
+            ForwardItem::Struct(item) => &item.vis,
+        }
+    }
+
+    pub fn generics(&self) -> &Generics {
+        match self {
+            ForwardItem::Enum(item) => &item.generics,
+            ForwardItem::Struct(item) => &item.generics,
+        }
+    }
+
+    /// Get the arms of a match on the item. Variants that can't be forwarded are reported to
+    /// `errors` and left out
+    pub fn arms(&self, errors: &mut Errors) -> Vec<ForwardArm<'_>> {
+        match self {
+            ForwardItem::Enum(item) => {
+                variant_patterns(item).filter_map(|v| {
+                    let VariantInfo { variant, inner_ty, pattern } = errors.check(v)?;
+                    Some(ForwardArm { variant: Some(variant), inner_ty, pattern })
+                }).collect()
+            }
+            ForwardItem::Struct(item) => {
+                errors.check(field_pattern(item)).map(|FieldInfo { inner_ty, pattern, .. }| {
+                    ForwardArm { variant: None, inner_ty, pattern }
+                }).into_iter().collect()
+            }
+        }
+    }
+
+    /// A catch-all arm for the variants left out of [ForwardItem::arms], so matches stay
+    /// exhaustive while their errors are reported
+    pub fn fallback_arm(&self, arms: &[ForwardArm]) -> Option<TokenStream> {
+        let count = match self {
+            ForwardItem::Enum(item) => item.variants.len(),
+            ForwardItem::Struct(_) => 1,
+        };
+        fallback_arm(arms.len(), count)
+    }
+}
+
+/// A catch-all arm for a match that only has `arms` of its `count` arms
+pub(crate) fn fallback_arm(arms: usize, count: usize) -> Option<TokenStream> {
+    (arms < count).then(|| quote!(_ => ::core::unreachable!()))
+}
+
+/// The options set on an item with `#[enum_forward(...)]`
+pub(crate) struct ItemOptions {
+    /// the path generated code uses to refer to the enum-forward crate, which is `::enum_forward`
+    /// unless overridden with `crate = path`
+    pub krate: Path,
+    /// the hints added to generated methods with `inline = ...`, `track_caller` and `cold`
+    pub hints: MethodHints,
+}
+
+impl Default for ItemOptions {
+    fn default() -> Self {
+        ItemOptions { krate: parse_quote!(::enum_forward), hints: MethodHints::default() }
+    }
+}
+
+/// Get the hints added to generated methods
+pub(crate) fn method_hints(attrs: &[Attribute]) -> Result<MethodHints> {
+    Ok(item_options(attrs)?.hints)
+}
+
+/// Parse the `#[enum_forward(...)]` options of an item
+pub(crate) fn item_options(attrs: &[Attribute]) -> Result<ItemOptions> {
+    let mut krate = parse_quote!(::enum_forward);
+    let mut hints = MethodHints::default();
+    for attr in attrs.iter().filter(|a| a.path().is_ident("enum_forward")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("crate") {
+                krate = meta.value()?.parse()?;
+            } else if meta.path.is_ident("inline") {
+                if meta.input.peek(Token!(=)) {
+                    hints.inline = Some(meta.value()?.parse()?);
+                } else {
+                    meta.parse_nested_meta(|method| {
+                        let ident = method.path.require_ident()?.clone();
+                        hints.inline_methods.push((ident, method.value()?.parse()?));
+                        Ok(())
+                    })?;
+                }
+            } else if meta.path.is_ident("track_caller") {
+                hints.track_caller.parse(&meta)?;
+            } else if meta.path.is_ident("cold") {
+                hints.cold.parse(&meta)?;
+            } else {
+                return Err(meta.error("Unknown enum_forward option"));
+            }
+            Ok(())
+        })?;
+    }
+    Ok(ItemOptions { krate, hints })
+}
+
+/// An inlining hint for generated methods
+#[derive(Clone, Copy, PartialEq)]
+pub(crate) enum Inline {
+    Always,
+    Never,
+    /// whatever the macro would generate without a hint
+    Default,
+}
+
+impl Parse for Inline {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let ident: Ident = input.parse()?;
+        match ident.to_string().as_str() {
+            "always" => Ok(Inline::Always),
+            "never" => Ok(Inline::Never),
+            "default" => Ok(Inline::Default),
+            _ => Err(syn::Error::new(ident.span(), "Unknown inline hint, expected `always`, `never` or `default`")),
+        }
+    }
+}
+
+/// The generated methods an attribute applies to, either all of them or those listed like
+/// `cold(fmt, forward)`
+#[derive(Default)]
+pub(crate) struct HintMethods {
+    all: bool,
+    methods: Vec<Ident>,
+}
+
+impl HintMethods {
+    fn parse(&mut self, meta: &ParseNestedMeta) -> syn::Result<()> {
+        if meta.input.peek(Paren) {
+            meta.parse_nested_meta(|method| {
+                self.methods.push(method.path.require_ident()?.clone());
+                Ok(())
+            })
+        } else {
+            self.all = true;
+            Ok(())
+        }
+    }
+
+    fn contains(&self, method: &str) -> bool {
+        self.all || self.methods.iter().any(|m| m == method)
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct MethodHints {
+    inline: Option<Inline>,
+    inline_methods: Vec<(Ident, Inline)>,
+    track_caller: HintMethods,
+    cold: HintMethods,
+}
+
+impl MethodHints {
+    fn is_empty(&self) -> bool {
+        self.inline.is_none() && self.inline_methods.is_empty() && !self.track_caller.all
+            && self.track_caller.methods.is_empty() && !self.cold.all && self.cold.methods.is_empty()
+    }
+
+    /// Apply the hints to the methods of each impl, and the provided methods of each trait, in
+    /// generated code, in place of the inlining hints they're generated with
+    pub fn apply(&self, output: TokenStream) -> Result<TokenStream> {
+        if self.is_empty() {
+            return Ok(output);
+        }
+        let mut file = parse2::<File>(output).map_err(|err| Error::Other(err.span(), format!(
+            "Failed to apply the `enum_forward` method hints to the generated code: {}", err)))?;
+        for item in &mut file.items {
+            match item {
+                Item::Impl(item_impl) => {
+                    for impl_item in &mut item_impl.items {
+                        if let ImplItem::Fn(method) = impl_item {
+                            self.apply_to(&method.sig.ident.to_string(), &mut method.attrs);
+                        }
+                    }
+                }
+                Item::Trait(item_trait) => {
+                    for trait_item in &mut item_trait.items {
+                        if let TraitItem::Fn(method @ TraitItemFn { default: Some(_), .. }) = trait_item {
+                            self.apply_to(&method.sig.ident.to_string(), &mut method.attrs);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        Ok(file.into_token_stream())
+    }
+
+    fn apply_to(&self, method: &str, attrs: &mut Vec<Attribute>) {
+        let inline = self.inline_methods.iter().rev()
+            .find(|(m, _)| m == method)
+            .map(|(_, inline)| *inline)
+            .or(self.inline);
+        match inline {
+            Some(Inline::Always) => {
+                attrs.retain(|a| !a.path().is_ident("inline"));
+                attrs.push(parse_quote!(#[inline(always)]));
+            }
+            Some(Inline::Never) => {
+                attrs.retain(|a| !a.path().is_ident("inline"));
+                attrs.push(parse_quote!(#[inline(never)]));
+            }
+            Some(Inline::Default) | None => {}
+        }
+        for (hint, methods) in [("track_caller", &self.track_caller), ("cold", &self.cold)] {
+            if methods.contains(method) && !attrs.iter().any(|a| a.path().is_ident(hint)) {
+                let hint = format_ident!("{}", hint);
+                attrs.push(parse_quote!(#[#hint]));
+            }
+        }
+    }
+}
+
+/// Check whether a variant is marked with `#[enum_forward(skip)]`, leaving it out of conversions
+pub(crate) fn skipped(attrs: &[Attribute]) -> Result<bool> {
+    let mut skip = false;
+    for attr in attrs.iter().filter(|a| a.path().is_ident("enum_forward")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("skip") {
+                skip = true;
+                Ok(())
+            } else {
+                Err(meta.error("Unknown enum_forward variant option"))
+            }
+        })?;
+    }
+    Ok(skip)
+}
+
+/// Paths in the standard library preludes, without their leading crate
+const PRELUDE: &[&str] = &[
+    "boxed::Box", "option::Option", "result::Result", "string::String", "vec::Vec",
+];
+
+/// Rewrite a type so that different spellings of the same type compare equal, e.g.
+/// `std::string::String` and `String`, or types wrapped in invisible groups by `macro_rules`
+pub(crate) fn normalize_type(ty: &Type) -> Type {
+    let mut ty = ty.clone();
+    NormalizeType.visit_type_mut(&mut ty);
+    ty
+}
+
+struct NormalizeType;
+
+impl VisitMut for NormalizeType {
+    fn visit_type_mut(&mut self, ty: &mut Type) {
+        while let Type::Group(TypeGroup { elem, .. }) | Type::Paren(TypeParen { elem, .. }) = ty {
+            *ty = (**elem).clone();
+        }
+        visit_mut::visit_type_mut(self, ty);
+    }
+
+    fn visit_path_mut(&mut self, path: &mut Path) {
+        visit_mut::visit_path_mut(self, path);
+        path.leading_colon = None;
+
+        let root = path.segments[0].ident.to_string();
+        if path.segments.len() > 1 && ["std", "core", "alloc"].contains(&root.as_str()) {
+            let rest = path.segments.iter().skip(1).map(|s| s.ident.to_string()).collect::<Vec<_>>();
+            if rest[0] == "primitive" || PRELUDE.contains(&rest.join("::").as_str()) {
+                let last = path.segments.pop().unwrap().into_value();
+                path.segments.clear();
+                path.segments.push(last);
+            } else {
+                // `std` and `alloc` re-export `core`, so use one name for all of them
+                path.segments[0].ident = Ident::new("core", path.segments[0].ident.span());
+            }
+        }
+    }
+
+    fn visit_angle_bracketed_generic_arguments_mut(&mut self, args: &mut AngleBracketedGenericArguments) {
+        visit_mut::visit_angle_bracketed_generic_arguments_mut(self, args);
+        args.colon2_token = None;
+    }
+}
+
+/// Print tokens roughly the way they would be written by hand, for use in error messages
+pub(crate) fn display_tokens(tokens: &impl ToTokens) -> String {
+    let mut s = tokens.to_token_stream().to_string();
+    for (from, to) in [(" :: ", "::"), (":: ", "::"), (" : ", ": "), ("& ", "&"), (" <", "<"), ("< ", "<"), (" >", ">"), (" ,", ","), (" ;", ";")] {
+        s = s.replace(from, to);
+    }
+    s
+}
+
+/// Collect every identifier used in a token stream
+pub(crate) fn token_idents(ts: TokenStream) -> HashSet<String> {
+    let mut idents = HashSet::new();
+    for tt in ts {
+        match tt {
+            TokenTree::Ident(i) => {
+                idents.insert(i.to_string());
+            }
+            TokenTree::Group(g) => {
+                idents.extend(token_idents(g.stream()));
+            }
+            _ => {}
+        }
+    }
+    idents
+}
+
+/// Declare a hidden `macro_rules` macro expanding to `body`, and import it under the name of the
+/// item it belongs to, so that importing the item also imports the macro. Macros of public items
+/// need `#[macro_export]` to be usable from other crates, which puts them in the crate root, so
+/// they get a name made from a hash of the item and a count of the macros declared so far, which
+/// tells apart identical items in different modules.
+pub(crate) fn item_macro(item_ident: &Ident, vis: &Visibility, item: &impl ToTokens, body: TokenStream) -> TokenStream {
+    static DECLARED: AtomicUsize = AtomicUsize::new(0);
+
+    let (macro_ident, export) = match vis {
+        Visibility::Public(_) => {
+            let count = DECLARED.fetch_add(1, Ordering::Relaxed);
+            let hash = fnv1a(format!("{}#{}", item.to_token_stream(), count).as_bytes());
+            (format_ident!("__enum_forward_{}_{:016x}", item_ident, hash), quote!(#[macro_export]))
+        }
+        _ => (format_ident!("__enum_forward_{}", item_ident), TokenStream::new()),
+    };
+
+    quote! {
+        #[doc(hidden)]
+        #export
+        macro_rules! #macro_ident {
+            #body
+        }
+
+        #[doc(hidden)]
+        #[allow(unused_imports)]
+        #vis use #macro_ident as #item_ident;
+    }
+}
+
+/// The 64 bit FNV-1a hash of `bytes`, which unlike `DefaultHasher` is the same on every Rust release
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, &b| (hash ^ b as u64).wrapping_mul(0x100000001b3))
+}
+
+/// Create an identifier starting with `base` that isn't already in `taken`, and add it
+pub(crate) fn fresh_ident(base: &str, taken: &mut HashSet<String>) -> Ident {
+    let ident = (0usize..).map(|i| match i {
+        0 => format_ident!("{}", base),
+        i => format_ident!("{}{}", base, i),
+    }).find(|i| !taken.contains(&i.to_string())).unwrap();
+    taken.insert(ident.to_string());
+    ident
+}
+
+pub(crate) fn replace_ident(ts: TokenStream, from: &Ident, to: &Ident) -> TokenStream {
+    let mut after_apostrophe = false;
     ts.into_iter().map(
         |tt| {
-            match tt {
+            let is_apostrophe = matches!(&tt, TokenTree::Punct(p) if p.as_char() == '\'');
+            let tt = match tt {
                 TokenTree::Group(g) => {
-                    TokenTree::Group(Group::new(g.delimiter(), replace_ident(g.stream(), from.clone(), to.clone())))
+                    let mut group = Group::new(g.delimiter(), replace_ident(g.stream(), from, to));
+                    group.set_span(g.span());
+                    TokenTree::Group(group)
                 }
-                TokenTree::Ident(i) if i.to_string() == to.to_string() => {
-                    TokenTree::Ident(to.clone())
+                // lifetimes are replaced with `replace_lifetime`
+                TokenTree::Ident(i) if i == *from && !after_apostrophe => {
+                    TokenTree::Ident(Ident::new(&to.to_string(), i.span()))
                 }
                 other => other
-            }
+            };
+            after_apostrophe = is_apostrophe;
+            tt
+        }
+    ).collect()
+}
+
+pub(crate) fn replace_lifetime(ts: TokenStream, from: &Lifetime, to: &Lifetime) -> TokenStream {
+    let mut after_apostrophe = false;
+    ts.into_iter().map(
+        |tt| {
+            let is_apostrophe = matches!(&tt, TokenTree::Punct(p) if p.as_char() == '\'');
+            let tt = match tt {
+                TokenTree::Group(g) => {
+                    let mut group = Group::new(g.delimiter(), replace_lifetime(g.stream(), from, to));
+                    group.set_span(g.span());
+                    TokenTree::Group(group)
+                }
+                TokenTree::Ident(i) if i == from.ident && after_apostrophe => {
+                    TokenTree::Ident(Ident::new(&to.ident.to_string(), i.span()))
+                }
+                other => other
+            };
+            after_apostrophe = is_apostrophe;
+            tt
         }
     ).collect()
 }
 
+/// The types that need a bound in the where clause. Concrete types are left out, since bounds
+/// on them overflow for recursive enums, and are checked where they are used anyways
+pub(crate) fn generic_types<'a>(generics: &Generics, tys: impl Iterator<Item=&'a Type>) -> Vec<&'a Type> {
+    let params = type_params(generics).iter().map(|p| p.to_string()).collect();
+    tys.unique().filter(|ty| !token_idents(ty.to_token_stream()).is_disjoint(&params)).collect()
+}
+
+/// Get the identifiers of the type and const parameters of some generics
+pub(crate) fn type_params(generics: &Generics) -> Vec<Ident> {
+    generics.params.iter().filter_map(|p| match p {
+        GenericParam::Type(t) => Some(t.ident.clone()),
+        GenericParam::Const(c) => Some(c.ident.clone()),
+        GenericParam::Lifetime(_) => None,
+    }).collect()
+}
+
+/// Check if two types could be the same type once any of the generic parameters in `params`
+/// are substituted, such that implementing a trait for both would conflict
+pub(crate) fn types_overlap(a: &Type, b: &Type, params: &[Ident]) -> bool {
+    let is_param = |ty: &Type| match ty {
+        // projections like `T::Item` could be anything
+        Type::Path(p) => p.qself.is_some() || params.contains(&p.path.segments[0].ident),
+        _ => false,
+    };
+
+    match (a, b) {
+        (Type::Paren(a), b) => types_overlap(&a.elem, b, params),
+        (Type::Group(a), b) => types_overlap(&a.elem, b, params),
+        (a, Type::Paren(b)) => types_overlap(a, &b.elem, params),
+        (a, Type::Group(b)) => types_overlap(a, &b.elem, params),
+        (a, b) if is_param(a) || is_param(b) => true,
+        (Type::Path(a), Type::Path(b)) => {
+            a.path.segments.len() == b.path.segments.len() &&
+                a.path.segments.iter().zip(&b.path.segments).all(|(a, b)| {
+                    a.ident == b.ident && match (&a.arguments, &b.arguments) {
+                        (PathArguments::None, PathArguments::None) => true,
+                        (PathArguments::AngleBracketed(a), PathArguments::AngleBracketed(b)) => {
+                            a.args.len() == b.args.len() &&
+                                a.args.iter().zip(&b.args).all(|(a, b)| match (a, b) {
+                                    (GenericArgument::Lifetime(_), GenericArgument::Lifetime(_)) => true,
+                                    (GenericArgument::Type(a), GenericArgument::Type(b)) => types_overlap(a, b, params),
+                                    (a, b) => consts_overlap(a, b, params),
+                                })
+                        }
+                        (a, b) => a.to_token_stream().to_string() == b.to_token_stream().to_string(),
+                    }
+                })
+        }
+        (Type::Reference(a), Type::Reference(b)) => {
+            a.mutability.is_some() == b.mutability.is_some() && types_overlap(&a.elem, &b.elem, params)
+        }
+        (Type::Ptr(a), Type::Ptr(b)) => {
+            a.mutability.is_some() == b.mutability.is_some() && types_overlap(&a.elem, &b.elem, params)
+        }
+        (Type::Slice(a), Type::Slice(b)) => types_overlap(&a.elem, &b.elem, params),
+        (Type::Array(a), Type::Array(b)) => {
+            types_overlap(&a.elem, &b.elem, params) && consts_overlap(&a.len, &b.len, params)
+        }
+        (Type::Tuple(a), Type::Tuple(b)) => {
+            a.elems.len() == b.elems.len() && a.elems.iter().zip(&b.elems).all(|(a, b)| types_overlap(a, b, params))
+        }
+        (a, b) => a.to_token_stream().to_string() == b.to_token_stream().to_string(),
+    }
+}
+
+fn consts_overlap(a: &impl ToTokens, b: &impl ToTokens, params: &[Ident]) -> bool {
+    let (a, b) = (a.to_token_stream(), b.to_token_stream());
+    let is_param = |ts: &TokenStream| !token_idents(ts.clone()).is_disjoint(&params.iter().map(|p| p.to_string()).collect());
+    is_param(&a) || is_param(&b) || a.to_string() == b.to_string()
+}
+
 pub(crate) fn lifetimeify(ty: Type, blanket: &Lifetime, lifetimes: &mut HashSet<Lifetime>) -> Type {
     match ty {
         Type::Array(inner) => {
@@ -118,4 +656,20 @@ pub(crate) fn lifetimeify(ty: Type, blanket: &Lifetime, lifetimes: &mut HashSet<
         }
         _ => { ty }
     }
-}
\ No newline at end of file
+}
+
+/// Convert an identifier in `UpperCamelCase` to `snake_case`
+pub(crate) fn snake_case(s: &str) -> String {
+    let mut snake = String::new();
+    for (i, c) in s.char_indices() {
+        if c.is_uppercase() {
+            if i > 0 && !s[..i].ends_with('_') {
+                snake.push('_');
+            }
+            snake.extend(c.to_lowercase());
+        } else {
+            snake.push(c);
+        }
+    }
+    snake
+}
@@ -0,0 +1,357 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use itertools::Itertools;
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote, ToTokens};
+use syn::{Attribute, Ident, ItemEnum, parse2, Path, Type};
+use syn::spanned::Spanned;
+
+use crate::common::{fallback_arm, fresh_ident, generic_types, method_hints, normalize_type, replace_ident, skipped, token_idents, type_params, types_overlap, variant_patterns, VariantInfo};
+use crate::error::{Error, Errors, Result};
+
+/// A comparison trait implemented by forwarding to the inner values of an enum
+#[derive(Clone, Copy)]
+pub(crate) enum CmpTrait {
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+}
+
+/// How values of different variants compare, set with `#[forward_cmp(...)]`
+enum CmpPolicy {
+    /// Variants are ordered by their declaration, and values of the same variant by their inner
+    /// values. This is the default
+    Declaration,
+    /// Every variant is compared by a key extracted with a `Trait::method`
+    Key { method: Path, trait_path: Path },
+}
+
+fn cmp_policy(attrs: &[Attribute]) -> Result<CmpPolicy> {
+    let mut policy = CmpPolicy::Declaration;
+    for attr in attrs.iter().filter(|a| a.path().is_ident("forward_cmp")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("order") {
+                let order: Ident = meta.value()?.parse()?;
+                if order != "declaration" {
+                    return Err(syn::Error::new(order.span(), "Unknown order, expected `declaration`"));
+                }
+                policy = CmpPolicy::Declaration;
+                Ok(())
+            } else if meta.path.is_ident("key") {
+                let method: Path = meta.value()?.parse()?;
+                if method.segments.len() < 2 {
+                    return Err(syn::Error::new(method.span(), "Expected a method path like `Trait::method`"));
+                }
+                let mut trait_path = method.clone();
+                trait_path.segments.pop();
+                trait_path.segments.pop_punct();
+                policy = CmpPolicy::Key { method, trait_path };
+                Ok(())
+            } else {
+                Err(meta.error("Unknown forward_cmp option"))
+            }
+        })?;
+    }
+    Ok(policy)
+}
+
+/// One arm of a comparison. Skipped variants are compared by their position alone, unless a
+/// key is needed from them
+struct CmpArm<'a> {
+    inner_ty: Option<&'a Type>,
+    pattern: TokenStream,
+}
+
+pub(crate) fn derive_compare2(item: TokenStream, cmp: CmpTrait) -> Result<TokenStream> {
+    let item = parse2::<ItemEnum>(item)?;
+    let item_ident = &item.ident;
+    let mut errors = Errors::default();
+    let policy = errors.check(cmp_policy(&item.attrs)).unwrap_or(CmpPolicy::Declaration);
+    let hints = errors.check(method_hints(&item.attrs)).unwrap_or_default();
+    let is_key = matches!(policy, CmpPolicy::Key { .. });
+
+    let mut arms = vec![];
+    let mut compared = vec![];
+    for (variant, v) in item.variants.iter().zip(variant_patterns(&item)) {
+        let Some(skip) = errors.check(skipped(&variant.attrs)) else {
+            continue;
+        };
+        if skip && !is_key {
+            let var_ident = &variant.ident;
+            arms.push(CmpArm { inner_ty: None, pattern: quote!(#item_ident::#var_ident { .. }) });
+            continue;
+        }
+        let Some(info) = errors.check(v) else {
+            continue;
+        };
+        arms.push(CmpArm { inner_ty: Some(info.inner_ty), pattern: info.pattern.clone() });
+        if !skip {
+            compared.push(info);
+        }
+    }
+    let fallback = fallback_arm(arms.len(), item.variants.len());
+
+    // comparisons against bare inner values need one variant per type. The `PartialEq` derive
+    // reports this, the others follow along quietly
+    let mut type_errors = Errors::default();
+    let compared = comparable_types(&item, compared, match cmp {
+        CmpTrait::PartialEq => &mut errors,
+        _ => &mut type_errors,
+    });
+
+    let mut taken = token_idents(item.to_token_stream());
+    let other_ident = fresh_ident("other", &mut taken);
+    let hasher = fresh_ident("H", &mut taken);
+    let value = format_ident!("value");
+
+    // the position of each variant, for comparing values of different variants
+    let index = |of: &TokenStream| {
+        let index_arms = item.variants.iter().enumerate().map(|(i, v)| {
+            let var_ident = &v.ident;
+            quote!(#item_ident::#var_ident { .. } => #i)
+        });
+        quote!(match #of { #(#index_arms,)* })
+    };
+    let index_of = |var: &Ident| item.variants.iter().position(|v| &v.ident == var).unwrap();
+    let key = |of: &TokenStream| {
+        let CmpPolicy::Key { method, .. } = &policy else {
+            unreachable!()
+        };
+        let key_arms = arms.iter().map(|a| {
+            let pattern = &a.pattern;
+            quote!(#pattern => #method(value))
+        });
+        quote!(match #of { #(#key_arms,)* #fallback })
+    };
+
+    let cmp_trait = match cmp {
+        CmpTrait::PartialEq => quote!(::core::cmp::PartialEq),
+        CmpTrait::Eq => quote!(::core::cmp::Eq),
+        CmpTrait::PartialOrd => quote!(::core::cmp::PartialOrd),
+        CmpTrait::Ord => quote!(::core::cmp::Ord),
+        CmpTrait::Hash => quote!(::core::hash::Hash),
+    };
+    // with a key, the inner values only need to provide it
+    let bound = match &policy {
+        CmpPolicy::Declaration => cmp_trait.clone(),
+        CmpPolicy::Key { trait_path, .. } => trait_path.to_token_stream(),
+    };
+
+    let (_, ty_generics, _) = item.generics.split_for_impl();
+    let mut generics = item.generics.clone();
+    let where_clause = generics.make_where_clause();
+    for ty in generic_types(&item.generics, arms.iter().filter_map(|a| a.inner_ty)) {
+        where_clause.predicates.push(parse2(quote!(#ty : #bound))?);
+    }
+    let (impl_generics, _, where_clause) = generics.split_for_impl();
+
+    // arms matching `(self, other)` for values of the same variant
+    let pair_arms = |same: TokenStream, skipped: TokenStream| arms.iter().map(|a| {
+        let pattern = &a.pattern;
+        let other_pattern = replace_ident(pattern.clone(), &value, &other_ident);
+        let body = if a.inner_ty.is_some() { &same } else { &skipped };
+        quote!((#pattern, #other_pattern) => #body)
+    }).collect_vec();
+    let pair_fallback = |different: TokenStream| {
+        if item.variants.len() > 1 {
+            Some(quote!(_ => #different))
+        } else {
+            fallback_arm(arms.len(), item.variants.len())
+        }
+    };
+    let self_ts = quote!(self);
+    let other_ts = other_ident.to_token_stream();
+
+    let body = match (cmp, &policy) {
+        (CmpTrait::Eq, _) => quote!(),
+        (CmpTrait::PartialEq, CmpPolicy::Declaration) => {
+            let same = pair_arms(quote!(::core::cmp::PartialEq::eq(value, #other_ident)), quote!(true));
+            let different = pair_fallback(quote!(false));
+            quote! {
+                fn eq(&self, #other_ident : &Self) -> bool {
+                    match (self, #other_ident) {
+                        #(#same,)*
+                        #different
+                    }
+                }
+            }
+        }
+        (CmpTrait::PartialEq, CmpPolicy::Key { .. }) => {
+            let (key_self, key_other) = (key(&self_ts), key(&other_ts));
+            quote! {
+                fn eq(&self, #other_ident : &Self) -> bool {
+                    ::core::cmp::PartialEq::eq(&#key_self, &#key_other)
+                }
+            }
+        }
+        (CmpTrait::PartialOrd, CmpPolicy::Declaration) => {
+            let same = pair_arms(quote!(::core::cmp::PartialOrd::partial_cmp(value, #other_ident)),
+                                 quote!(::core::option::Option::Some(::core::cmp::Ordering::Equal)));
+            let (index_self, index_other) = (index(&self_ts), index(&other_ts));
+            let different = pair_fallback(quote!(::core::cmp::PartialOrd::partial_cmp(&#index_self, &#index_other)));
+            quote! {
+                fn partial_cmp(&self, #other_ident : &Self) -> ::core::option::Option<::core::cmp::Ordering> {
+                    match (self, #other_ident) {
+                        #(#same,)*
+                        #different
+                    }
+                }
+            }
+        }
+        (CmpTrait::PartialOrd, CmpPolicy::Key { .. }) => {
+            let (key_self, key_other) = (key(&self_ts), key(&other_ts));
+            quote! {
+                fn partial_cmp(&self, #other_ident : &Self) -> ::core::option::Option<::core::cmp::Ordering> {
+                    ::core::cmp::PartialOrd::partial_cmp(&#key_self, &#key_other)
+                }
+            }
+        }
+        (CmpTrait::Ord, CmpPolicy::Declaration) => {
+            let same = pair_arms(quote!(::core::cmp::Ord::cmp(value, #other_ident)),
+                                 quote!(::core::cmp::Ordering::Equal));
+            let (index_self, index_other) = (index(&self_ts), index(&other_ts));
+            let different = pair_fallback(quote!(::core::cmp::Ord::cmp(&#index_self, &#index_other)));
+            quote! {
+                fn cmp(&self, #other_ident : &Self) -> ::core::cmp::Ordering {
+                    match (self, #other_ident) {
+                        #(#same,)*
+                        #different
+                    }
+                }
+            }
+        }
+        (CmpTrait::Ord, CmpPolicy::Key { .. }) => {
+            let (key_self, key_other) = (key(&self_ts), key(&other_ts));
+            quote! {
+                fn cmp(&self, #other_ident : &Self) -> ::core::cmp::Ordering {
+                    ::core::cmp::Ord::cmp(&#key_self, &#key_other)
+                }
+            }
+        }
+        (CmpTrait::Hash, CmpPolicy::Declaration) => {
+            let index_self = index(&self_ts);
+            let hash_arms = arms.iter().map(|a| {
+                let pattern = &a.pattern;
+                match a.inner_ty {
+                    Some(_) => quote!(#pattern => ::core::hash::Hash::hash(value, state)),
+                    None => quote!(#pattern => {}),
+                }
+            });
+            quote! {
+                fn hash<#hasher : ::core::hash::Hasher>(&self, state : &mut #hasher) {
+                    ::core::hash::Hash::hash(&#index_self, state);
+                    match self {
+                        #(#hash_arms,)*
+                        #fallback
+                    }
+                }
+            }
+        }
+        (CmpTrait::Hash, CmpPolicy::Key { .. }) => {
+            let key_self = key(&self_ts);
+            quote! {
+                fn hash<#hasher : ::core::hash::Hasher>(&self, state : &mut #hasher) {
+                    ::core::hash::Hash::hash(&#key_self, state);
+                }
+            }
+        }
+    };
+
+    let mut output = quote! {
+        impl #impl_generics #cmp_trait for #item_ident #ty_generics #where_clause {
+            #body
+        }
+    };
+
+    // comparisons against a bare inner value, which compares as if it was in its variant
+    if matches!(cmp, CmpTrait::PartialEq | CmpTrait::PartialOrd) {
+        let index_self = index(&self_ts);
+        for VariantInfo { variant, inner_ty, pattern } in &compared {
+            let (_, ty_generics, _) = item.generics.split_for_impl();
+            let mut generics = item.generics.clone();
+            let where_clause = generics.make_where_clause();
+            for ty in generic_types(&item.generics, [*inner_ty].into_iter()) {
+                where_clause.predicates.push(parse2(quote!(#ty : #bound))?);
+            }
+            let (impl_generics, _, where_clause) = generics.split_for_impl();
+            let variant_index = index_of(&variant.ident);
+            let wildcard = item.variants.len() > 1;
+
+            let method = match (cmp, &policy) {
+                (CmpTrait::PartialEq, CmpPolicy::Declaration) => {
+                    let different = wildcard.then(|| quote!(_ => false));
+                    quote! {
+                        fn eq(&self, #other_ident : &#inner_ty) -> bool {
+                            match self {
+                                #pattern => ::core::cmp::PartialEq::eq(value, #other_ident),
+                                #different
+                            }
+                        }
+                    }
+                }
+                (CmpTrait::PartialEq, CmpPolicy::Key { method, .. }) => {
+                    let key_self = key(&self_ts);
+                    quote! {
+                        fn eq(&self, #other_ident : &#inner_ty) -> bool {
+                            ::core::cmp::PartialEq::eq(&#key_self, &#method(#other_ident))
+                        }
+                    }
+                }
+                (_, CmpPolicy::Declaration) => {
+                    let different = wildcard.then(|| quote! {
+                        _ => ::core::cmp::PartialOrd::partial_cmp(&#index_self, &#variant_index)
+                    });
+                    quote! {
+                        fn partial_cmp(&self, #other_ident : &#inner_ty) -> ::core::option::Option<::core::cmp::Ordering> {
+                            match self {
+                                #pattern => ::core::cmp::PartialOrd::partial_cmp(value, #other_ident),
+                                #different
+                            }
+                        }
+                    }
+                }
+                (_, CmpPolicy::Key { method, .. }) => {
+                    let key_self = key(&self_ts);
+                    quote! {
+                        fn partial_cmp(&self, #other_ident : &#inner_ty) -> ::core::option::Option<::core::cmp::Ordering> {
+                            ::core::cmp::PartialOrd::partial_cmp(&#key_self, &#method(#other_ident))
+                        }
+                    }
+                }
+            };
+
+            output.extend(quote! {
+                impl #impl_generics #cmp_trait<#inner_ty> for #item_ident #ty_generics #where_clause {
+                    #method
+                }
+            });
+        }
+    }
+
+    Ok(errors.finish(hints.apply(output)?))
+}
+
+/// Pick the variants whose types can be compared against directly, reporting variants with the
+/// same type as another, since their impls would conflict
+fn comparable_types<'a>(item: &ItemEnum, variants: Vec<VariantInfo<'a>>, errors: &mut Errors) -> Vec<VariantInfo<'a>> {
+    let params = type_params(&item.generics);
+    let mut tys = Vec::<(&Type, Type)>::new();
+    variants.into_iter().filter(|v| {
+        let normalized = normalize_type(v.inner_ty);
+        if let Some((first, _)) = tys.iter().find(|(_, t)| t == &normalized) {
+            errors.push(Error::DuplicateType(Box::new(v.inner_ty.clone()), Box::new((*first).clone())));
+            return false;
+        }
+        if tys.iter().any(|(_, t)| types_overlap(t, &normalized, &params)) {
+            errors.push(Error::Other(v.inner_ty.span(), "Enum variant may have the same type as another variant, \
+            depending on its generic parameters".into()));
+            return false;
+        }
+        tys.push((v.inner_ty, normalized));
+        true
+    }).collect()
+}
@@ -2,14 +2,14 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
-use std::collections::HashSet;
 use proc_macro2::TokenStream;
-use quote::{quote, ToTokens};
-use syn::{ItemEnum, parse2};
+use quote::quote;
+use syn::{ItemEnum, parse2, Type};
 use syn::spanned::Spanned;
 
-use crate::common::{variant_patterns, VariantInfo};
-use crate::error::{Error, Result};
+use crate::common::{display_tokens, fallback_arm, item_options, ItemOptions, method_hints, normalize_type, skipped, type_params, types_overlap, variant_patterns, VariantInfo};
+use crate::error::{Error, Errors, Result};
+use crate::flatten::{flatten_call, flattened, register};
 
 pub(crate) fn derive_enum_from2(item: TokenStream) -> Result<TokenStream> {
     let item = parse2::<ItemEnum>(item)?;
@@ -17,11 +17,17 @@ pub(crate) fn derive_enum_from2(item: TokenStream) -> Result<TokenStream> {
     let (impl_generics, ty_generics, where_clause) = item.generics.split_for_impl();
 
     let mut output = TokenStream::new();
+    let mut errors = Errors::default();
+    let ItemOptions { krate, hints } = errors.check(item_options(&item.attrs)).unwrap_or_default();
 
-    let mut tys = HashSet::<String>::new();
+    let params = type_params(&item.generics);
+    let mut tys = Vec::<(&Type, Type)>::new();
 
-    for v in variant_patterns(&item) {
-        let VariantInfo { inner_ty, pattern, .. } = v.map_err(
+    for (variant, v) in item.variants.iter().zip(variant_patterns(&item)) {
+        if errors.check(skipped(&variant.attrs)) != Some(false) {
+            continue;
+        }
+        let Some(VariantInfo { inner_ty, pattern, .. }) = errors.check(v.map_err(
             |e| match e {
                 Error::UnitVariant(s) => {
                     // provide more info for unit variants in this case
@@ -31,27 +37,40 @@ pub(crate) fn derive_enum_from2(item: TokenStream) -> Result<TokenStream> {
                 }
                 _ => e.clone()
             }
-        )?;
+        )) else {
+            continue;
+        };
 
         // check for duplicate types. This will fail anyways due to duplicate From<T>
         // impls, but this error should be more readable
-        let inner_ty_name = inner_ty.to_token_stream().to_string();
-        if !tys.contains(&inner_ty_name) {
-            tys.insert(inner_ty_name);
-        } else {
-            return Err(Error::DuplicateType(inner_ty.span()));
+        let normalized = normalize_type(inner_ty);
+        if let Some((first, _)) = tys.iter().find(|(_, t)| t == &normalized) {
+            errors.push(Error::DuplicateType(Box::new(inner_ty.clone()), Box::new((*first).clone())));
+            continue;
+        }
+        if tys.iter().any(|(_, t)| types_overlap(t, &normalized, &params)) {
+            errors.push(Error::Other(inner_ty.span(), "Enum variant may have the same type as another variant, \
+            depending on its generic parameters".into()));
+            continue;
+        }
+        tys.push((inner_ty, normalized));
+
+        if errors.check(flattened(&variant.attrs)) == Some(true) {
+            output.extend(errors.check(flatten_call("From", &item, variant, inner_ty)));
         }
 
         output.extend(quote! {
             impl #impl_generics ::core::convert::From<#inner_ty> for #item_ident #ty_generics #where_clause {
-                fn from(value : #inner_ty) -> #item_ident {
+                fn from(value : #inner_ty) -> Self {
                     #pattern
                 }
             }
         });
     }
 
-    Ok(output)
+    output.extend(errors.check(register(&item, &krate)));
+
+    Ok(errors.finish(hints.apply(output)?))
 }
 
 pub(crate) fn derive_enum_tryinto2(item: TokenStream) -> Result<TokenStream> {
@@ -60,8 +79,10 @@ pub(crate) fn derive_enum_tryinto2(item: TokenStream) -> Result<TokenStream> {
     let (impl_generics, ty_generics, where_clause) = item.generics.split_for_impl();
 
     let mut output = TokenStream::new();
+    let mut errors = Errors::default();
 
-    let mut tys = HashSet::<String>::new();
+    let params = type_params(&item.generics);
+    let mut tys = Vec::<&Type>::new();
 
     let err_map = |e: Error| match e {
         Error::UnitVariant(s) => {
@@ -73,34 +94,65 @@ pub(crate) fn derive_enum_tryinto2(item: TokenStream) -> Result<TokenStream> {
         _ => e.clone()
     };
 
-    for v in variant_patterns(&item) {
-        let VariantInfo { inner_ty: try_ty, .. } = v.map_err(err_map)?;
+    // skipped variants can't be converted to any type, but still need an arm
+    let mut variants = vec![];
+    let mut skipped_arms = vec![];
+    for (variant, v) in item.variants.iter().zip(variant_patterns(&item)) {
+        match errors.check(skipped(&variant.attrs)) {
+            Some(false) => {
+                let Some(v) = errors.check(v.map_err(err_map)) else {
+                    continue;
+                };
+                if errors.check(flattened(&variant.attrs)) == Some(true) {
+                    output.extend(errors.check(flatten_call("TryInto", &item, variant, v.inner_ty)));
+                }
+                variants.push((normalize_type(v.inner_ty), v));
+            }
+            Some(true) => {
+                let var_ident = &variant.ident;
+                skipped_arms.push((quote!(#item_ident::#var_ident { .. }), var_ident));
+            }
+            None => {}
+        }
+    }
+    let fallback = fallback_arm(variants.len() + skipped_arms.len(), item.variants.len());
+
+    for (normalized, VariantInfo { inner_ty: try_ty, .. }) in &variants {
+        // `TryInto<T>` for a type parameter `T` would conflict with the blanket impl in core
+        if matches!(try_ty, Type::Path(p) if p.qself.is_none() && p.path.get_ident().is_some_and(|i| params.contains(i))) {
+            continue;
+        }
 
-        let try_ty_name = try_ty.to_token_stream().to_string();
         // check if this type has already been implemented
-        if !tys.contains(&try_ty_name) {
-            tys.insert(try_ty_name.clone());
-
-            let arms = variant_patterns(&item).map(
-                |v| -> Result<TokenStream> {
-                    let VariantInfo { variant, inner_ty, pattern, .. } = v.map_err(err_map)?;
-
-                    if inner_ty.to_token_stream().to_string() == try_ty.to_token_stream().to_string() {
-                        return Ok(quote!(#pattern => Ok(value)));
-                    } else {
-                        let msg = format!("Cannot convert {}::{} to {}", item_ident, variant.ident, try_ty_name);
-                        return Ok(quote!(#pattern => Err(#msg)));
-                    }
+        if !tys.contains(&normalized) {
+            if tys.iter().any(|t| types_overlap(t, normalized, &params)) {
+                errors.push(Error::Other(try_ty.span(), "Enum variant may have the same type as another variant, \
+                depending on its generic parameters".into()));
+                continue;
+            }
+            tys.push(normalized);
+
+            let try_ty_name = display_tokens(try_ty);
+            let arms = variants.iter().map(|(inner_ty, VariantInfo { variant, pattern, .. })| {
+                if inner_ty == normalized {
+                    quote!(#pattern => ::core::result::Result::Ok(value))
+                } else {
+                    let msg = format!("Cannot convert {}::{} to {}", item_ident, variant.ident, try_ty_name);
+                    quote!(#pattern => ::core::result::Result::Err(#msg))
                 }
-            ).collect::<Result::<Vec::<_>>>()?;
+            }).chain(skipped_arms.iter().map(|(pattern, var_ident)| {
+                let msg = format!("Cannot convert {}::{} to {}", item_ident, var_ident, try_ty_name);
+                quote!(#pattern => ::core::result::Result::Err(#msg))
+            }));
 
             output.extend(quote! {
             impl #impl_generics ::core::convert::TryInto<#try_ty> for #item_ident #ty_generics #where_clause {
                 type Error  = &'static str;
 
-                fn try_into(self) -> ::core::result::Result<#try_ty, <Self as core::convert::TryInto<#try_ty>>::Error> {
+                fn try_into(self) -> ::core::result::Result<#try_ty, <Self as ::core::convert::TryInto<#try_ty>>::Error> {
                     match self {
-                        #(#arms),*
+                        #(#arms,)*
+                        #fallback
                     }
                 }
             }
@@ -108,5 +160,6 @@ pub(crate) fn derive_enum_tryinto2(item: TokenStream) -> Result<TokenStream> {
         }
     }
 
-    Ok(output)
-}
\ No newline at end of file
+    let hints = errors.check(method_hints(&item.attrs)).unwrap_or_default();
+    Ok(errors.finish(hints.apply(output)?))
+}
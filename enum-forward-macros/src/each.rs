@@ -0,0 +1,93 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use itertools::Itertools;
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote, ToTokens};
+use syn::{GenericParam, Ident, ItemEnum, parse2, TypeParam};
+
+use crate::common::{fallback_arm, fresh_ident, item_options, ItemOptions, token_idents, variant_patterns, VariantInfo};
+use crate::error::{Errors, Result};
+
+/// Get the name of the output enum from `#[forward_each(output = Name)]`, which is `{Enum}Output`
+/// by default
+fn output_ident(item: &ItemEnum) -> Result<Ident> {
+    let mut ident = format_ident!("{}Output", item.ident);
+    for attr in item.attrs.iter().filter(|a| a.path().is_ident("forward_each")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("output") {
+                ident = meta.value()?.parse()?;
+                Ok(())
+            } else {
+                Err(meta.error("Unknown forward_each option"))
+            }
+        })?;
+    }
+    Ok(ident)
+}
+
+/// Generate an enum with the same variants holding each variant's own output, and implement
+/// `ForwardEach` to forward to the inner value and wrap its output in the matching variant
+pub(crate) fn derive_forward_each2(item: TokenStream) -> Result<TokenStream> {
+    let item = parse2::<ItemEnum>(item)?;
+    let item_ident = &item.ident;
+    let vis = &item.vis;
+    let mut errors = Errors::default();
+    let ItemOptions { krate, hints } = errors.check(item_options(&item.attrs)).unwrap_or_default();
+    let output_ident = errors.check(output_ident(&item)).unwrap_or_else(|| format_ident!("{}Output", item_ident));
+
+    let mut taken = token_idents(item.to_token_stream());
+    let input_ty = fresh_ident("I", &mut taken);
+
+    let variants = variant_patterns(&item).filter_map(|v| errors.check(v)).collect_vec();
+    let fallback = fallback_arm(variants.len(), item.variants.len());
+    let output_params = variants.iter()
+        .map(|v| fresh_ident(&format!("R{}", v.variant.ident), &mut taken))
+        .collect_vec();
+
+    let output_variants = variants.iter().zip(&output_params).map(|(VariantInfo { variant, .. }, param)| {
+        let var_ident = &variant.ident;
+        quote!(#var_ident(#param))
+    });
+    let doc = format!(" The output of forwarding each variant of [{}], from `ForwardEach`", item_ident);
+
+    let (_, ty_generics, _) = item.generics.split_for_impl();
+    let mut generics = item.generics.clone();
+    generics.params.push(GenericParam::Type(TypeParam::from(input_ty.clone())));
+    let where_clause = generics.make_where_clause();
+    for ty in variants.iter().map(|v| v.inner_ty).unique() {
+        where_clause.predicates.push(parse2(quote!(#ty : #krate::Forward<#input_ty>))?);
+    }
+    let (impl_generics, _, where_clause) = generics.split_for_impl();
+
+    let outputs = variants.iter().map(|v| {
+        let ty = v.inner_ty;
+        quote!(<#ty as #krate::Forward<#input_ty>>::Output)
+    });
+    let arms = variants.iter().map(|VariantInfo { variant, pattern, .. }| {
+        let var_ident = &variant.ident;
+        quote!(#pattern => #output_ident::#var_ident(#krate::Forward::forward(value, input)))
+    });
+
+    let output = quote! {
+        #[doc = #doc]
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        #vis enum #output_ident<#(#output_params),*> {
+            #(#output_variants,)*
+        }
+
+        impl #impl_generics #krate::ForwardEach<#input_ty> for #item_ident #ty_generics #where_clause {
+            type Output = #output_ident<#(#outputs),*>;
+
+            fn forward_each(&self, input : &#input_ty) -> Self::Output {
+                match self {
+                    #(#arms,)*
+                    #fallback
+                }
+            }
+        }
+    };
+
+    Ok(errors.finish(hints.apply(output)?))
+}
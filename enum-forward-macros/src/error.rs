@@ -2,21 +2,27 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
-use proc_macro2::{Span};
+use proc_macro2::{Span, TokenStream};
+use quote::ToTokens;
+use syn::Type;
+
+use crate::common::display_tokens;
 
 #[derive(Clone)]
 pub enum Error {
     MultipleMembers(Span),
     UnitVariant(Span),
-    DuplicateType(Span),
-    MismatchedArgType(Span),
+    /// a variant's type, and the type of the earlier variant it duplicates
+    DuplicateType(Box<Type>, Box<Type>),
+    MismatchedArgType { attr_ty: Box<Type>, arg_ty: Box<Type>, suggestion: String },
+    AmbiguousField(Span),
     Other(Span, String),
     Syn(syn::Error),
 }
 
-impl Into<syn::Error> for Error {
-    fn into(self) -> syn::Error {
-        match self {
+impl From<Error> for syn::Error {
+    fn from(value: Error) -> Self {
+        match value {
             Error::MultipleMembers(span) => {
                 syn::Error::new(span,
                                 "Enum variant has multiple members, and cannot be converted to or from an inner type")
@@ -25,11 +31,21 @@ impl Into<syn::Error> for Error {
                 syn::Error::new(span,
                                 "Enum variant is a unit variant, and cannot be converted to or from an inner type")
             }
-            Error::DuplicateType(span) => {
-                syn::Error::new(span, "Enum has multiple variants with the same type.")
+            Error::DuplicateType(ty, first) => {
+                let mut err = syn::Error::new_spanned(ty, "Enum has multiple variants with the same type. \
+                Mark all but one of them with `#[enum_forward(skip)]` to leave them out of the conversion");
+                err.combine(syn::Error::new_spanned(first, "note: the type is first used here"));
+                err
+            }
+            Error::MismatchedArgType { attr_ty, arg_ty, suggestion } => {
+                let mut err = syn::Error::new_spanned(&attr_ty, format!(
+                    "Mismatch between argument name and type: the argument has type `{}`, not `{}`. Try {}",
+                    display_tokens(&arg_ty), display_tokens(&attr_ty), suggestion));
+                err.combine(syn::Error::new_spanned(arg_ty, "note: the argument's type is declared here"));
+                err
             }
-            Error::MismatchedArgType(span) => {
-                syn::Error::new(span, "Mismatch between argument name and type")
+            Error::AmbiguousField(span) => {
+                syn::Error::new(span, "Struct has multiple fields to forward to. Mark one of them with `#[forward]`")
             }
             Error::Other(span, msg) => {
                 syn::Error::new(span, msg)
@@ -46,8 +62,52 @@ impl From<syn::Error> for Error {
 }
 
 impl Error {
-    pub fn to_compile_error(self) -> proc_macro::TokenStream {
-        <Self as Into<syn::Error>>::into(self).to_compile_error().into()
+    /// Point at other code related to the error. Notes can't have their own span on stable,
+    /// so they are reported as separate errors
+    pub fn note(self, tokens: impl ToTokens, msg: &str) -> Self {
+        let mut err = syn::Error::from(self);
+        err.combine(syn::Error::new_spanned(tokens, format!("note: {}", msg)));
+        Error::Syn(err)
+    }
+
+    pub fn into_compile_error(self) -> proc_macro::TokenStream {
+        syn::Error::from(self).to_compile_error().into()
+    }
+}
+
+/// Collects errors so every problem with an item is reported in one pass, instead of
+/// stopping at the first one
+#[derive(Default)]
+pub struct Errors(Option<syn::Error>);
+
+impl Errors {
+    pub fn push(&mut self, err: impl Into<syn::Error>) {
+        let err = err.into();
+        match &mut self.0 {
+            Some(errors) => errors.combine(err),
+            None => self.0 = Some(err),
+        }
+    }
+
+    /// Record the error if `result` is one, returning the value otherwise
+    pub fn check<T>(&mut self, result: Result<T>) -> Option<T> {
+        result.map_err(|e| self.push(e)).ok()
+    }
+
+    pub fn into_result(self) -> Result<()> {
+        match self.0 {
+            Some(err) => Err(Error::Syn(err)),
+            None => Ok(()),
+        }
+    }
+
+    /// Append the collected errors to the generated code, so the impls that could be
+    /// generated are still emitted and don't cause errors of their own
+    pub fn finish(self, mut output: TokenStream) -> TokenStream {
+        if let Some(err) = self.0 {
+            output.extend(err.to_compile_error());
+        }
+        output
     }
 }
 
@@ -0,0 +1,326 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Snapshots of the code each macro generates, so changes to it show up in review.
+//! Run with `INSTA_UPDATE=always` to accept new output.
+
+use insta::assert_snapshot;
+use proc_macro2::TokenStream;
+use quote::quote;
+
+use crate::compare::{CmpTrait, derive_compare2};
+use crate::convert::{derive_enum_from2, derive_enum_tryinto2};
+use crate::each::derive_forward_each2;
+use crate::error::Result;
+use crate::flatten::flatten_variant;
+use crate::forward::{forward_to, forwarding2};
+use crate::state::derive_state_machine2;
+use crate::visitor::derive_visitor2;
+
+fn pretty(output: Result<TokenStream>) -> String {
+    let output = output.unwrap_or_else(|e| syn::Error::from(e).to_compile_error());
+    prettyplease::unparse(&syn::parse2(output).expect("macro output is not a list of items"))
+}
+
+fn tuple_enum() -> TokenStream {
+    quote! {
+        enum Value {
+            Text(String),
+            Bytes(Vec<u8>),
+        }
+    }
+}
+
+fn named_enum() -> TokenStream {
+    quote! {
+        enum Shape {
+            Circle { circle: Circle },
+            Square { square: Square },
+        }
+    }
+}
+
+fn generic_enum() -> TokenStream {
+    quote! {
+        enum Buf<'a, T, const N: usize> where T: Clone {
+            Arr([T; N]),
+            Slice(&'a [T]),
+            Owned(Vec<T>),
+        }
+    }
+}
+
+#[test]
+fn from_tuple() {
+    assert_snapshot!(pretty(derive_enum_from2(tuple_enum())));
+}
+
+#[test]
+fn from_named() {
+    assert_snapshot!(pretty(derive_enum_from2(named_enum())));
+}
+
+#[test]
+fn from_generic() {
+    assert_snapshot!(pretty(derive_enum_from2(generic_enum())));
+}
+
+#[test]
+fn tryinto_tuple() {
+    assert_snapshot!(pretty(derive_enum_tryinto2(tuple_enum())));
+}
+
+#[test]
+fn tryinto_named() {
+    assert_snapshot!(pretty(derive_enum_tryinto2(named_enum())));
+}
+
+#[test]
+fn tryinto_generic() {
+    assert_snapshot!(pretty(derive_enum_tryinto2(generic_enum())));
+}
+
+#[test]
+fn compare_declaration() {
+    assert_snapshot!(pretty(derive_compare2(named_enum(), CmpTrait::PartialOrd)));
+}
+
+#[test]
+fn compare_key() {
+    assert_snapshot!(pretty(derive_compare2(quote! {
+        #[forward_cmp(key = Priority::priority)]
+        enum Task<T> {
+            Urgent(Urgent),
+            Other(T),
+        }
+    }, CmpTrait::Hash)));
+}
+
+#[test]
+fn flatten_from() {
+    assert_snapshot!(pretty(derive_enum_from2(quote! {
+        #[from(register)]
+        enum Item {
+            #[from(flatten)]
+            Shape(shapes::Shape<f32>),
+            Label(String),
+        }
+    })));
+}
+
+#[test]
+fn flatten_tryinto() {
+    assert_snapshot!(pretty(flatten_variant(quote! {
+        {
+            enum Shape<T> {
+                Circle(Circle<T>),
+                Square { square: Square<T> },
+                #[enum_forward(skip)]
+                Empty,
+            }
+        }
+        TryInto Shape; shapes::Shape<f32>;
+        enum Item {
+            #[from(flatten)]
+            Shape(shapes::Shape<f32>),
+            Label(String),
+        }
+    })));
+}
+
+#[test]
+fn forward_tuple() {
+    assert_snapshot!(pretty(forwarding2(tuple_enum())));
+}
+
+#[test]
+fn forward_named() {
+    assert_snapshot!(pretty(forwarding2(named_enum())));
+}
+
+#[test]
+fn forward_generic() {
+    assert_snapshot!(pretty(forwarding2(generic_enum())));
+}
+
+#[test]
+fn forward_struct() {
+    assert_snapshot!(pretty(forwarding2(quote! {
+        struct Logged<T> {
+            #[forward]
+            inner: T,
+            lines: Vec<String>,
+        }
+    })));
+}
+
+#[test]
+fn forward_traits() {
+    assert_snapshot!(pretty(forwarding2(quote! {
+        #[forward(fmt::Display, GetName)]
+        enum Value<'a> {
+            #[forward_override(GetName::name = text_name)]
+            Text(&'a str),
+            Number(u32),
+        }
+    })));
+}
+
+#[test]
+fn forward_bare_builtin() {
+    assert_snapshot!(pretty(forwarding2(quote! {
+        #[forward(Display, Neg, Debug)]
+        enum Value {
+            Text(String),
+            Number(i32),
+        }
+    })));
+}
+
+#[test]
+fn forward_any() {
+    assert_snapshot!(pretty(forwarding2(quote! {
+        #[forward(Any)]
+        pub enum Value<T> {
+            Text(String),
+            Other(T),
+        }
+    })));
+}
+
+#[test]
+fn forward_kind() {
+    assert_snapshot!(pretty(forwarding2(quote! {
+        #[forward(kind)]
+        pub enum Operand {
+            #[forward(flatten)]
+            Literal(Literal),
+            Register(u8),
+        }
+    })));
+}
+
+#[test]
+fn forward_replace_inner() {
+    assert_snapshot!(pretty(forwarding2(quote! {
+        #[forward(replace_inner)]
+        pub enum Reading {
+            #[default_variant]
+            Raw(u32),
+            Filtered { value: u32 },
+        }
+    })));
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn forward_dyn() {
+    assert_snapshot!(pretty(forwarding2(quote! {
+        #[forward(dyn(GetName))]
+        enum Value<T> {
+            Text(String),
+            Other(T),
+        }
+    })));
+}
+
+#[test]
+fn forward_to_method() {
+    assert_snapshot!(pretty(forward_to(
+        quote!(Foo as GetName),
+        quote!(fn is(&self, name: &str) -> bool;),
+    )));
+}
+
+#[test]
+fn forward_to_body() {
+    assert_snapshot!(pretty(forward_to(
+        quote!(value: &'a Foo as GetName + Clone),
+        quote! {
+            pub fn label<'a, T: Display>(prefix: T, value: &'a Foo) -> String {
+                format!("{}{}", prefix, self.name())
+            }
+        },
+    )));
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn serialize_generic() {
+    assert_snapshot!(pretty(crate::serialize::derive_serialize2(generic_enum())));
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn deserialize_named() {
+    assert_snapshot!(pretty(crate::serialize::derive_deserialize2(named_enum())));
+}
+
+#[test]
+fn forward_ops() {
+    assert_snapshot!(pretty(forwarding2(quote! {
+        #[forward(ops::Neg, ops::Add, ops::AddAssign<f32>)]
+        #[forward_ops(mismatch = error)]
+        enum Number<T> {
+            Float(f32),
+            Other(T),
+        }
+    })));
+}
+
+#[test]
+fn visitor() {
+    assert_snapshot!(pretty(derive_visitor2(quote! {
+        pub enum Node<'a, T> {
+            Leaf(T),
+            Branch { children: &'a [Node<'a, T>] },
+        }
+    })));
+}
+
+#[test]
+fn visitor_hints() {
+    assert_snapshot!(pretty(derive_visitor2(quote! {
+        #[enum_forward(inline = never, cold(visit_leaf))]
+        pub enum Node<T> {
+            Leaf(T),
+            Empty(()),
+        }
+    })));
+}
+
+#[test]
+fn state_machine() {
+    assert_snapshot!(pretty(derive_state_machine2(quote! {
+        #[state_machine(transitions(Idle => Open, Open => Idle | Closed))]
+        enum Conn {
+            Idle(Idle),
+            Open { open: Open },
+            Closed(Closed),
+        }
+    })));
+}
+
+#[test]
+fn method_hints() {
+    assert_snapshot!(pretty(forwarding2(quote! {
+        #[forward(Any)]
+        #[enum_forward(inline = always, inline(as_any_mut = default, forward = never), cold(dispatch), track_caller)]
+        enum Value {
+            Text(String),
+            Bytes(Vec<u8>),
+        }
+    })));
+}
+
+#[test]
+fn forward_each() {
+    assert_snapshot!(pretty(derive_forward_each2(quote! {
+        #[forward_each(output = Parsed)]
+        pub enum Literal<T> {
+            Int(IntText),
+            Other { value: T },
+        }
+    })));
+}
@@ -0,0 +1,270 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use itertools::Itertools;
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{Attribute, Fields, GenericArgument, GenericParam, Generics, Ident, ItemEnum, Lifetime, parse2, Path, PathArguments, Token, Type, Variant};
+use syn::parse::{Parse, ParseStream};
+use syn::spanned::Spanned;
+use syn::visit_mut::{self, VisitMut};
+
+use crate::common::{display_tokens, fallback_arm, item_macro, skipped, type_params, variant_patterns, VariantInfo};
+use crate::error::{Error, Result};
+
+/// Check whether a variant is marked with `#[from(flatten)]`, converting to and from the
+/// variants of the enum it contains
+pub(crate) fn flattened(attrs: &[Attribute]) -> Result<bool> {
+    let mut flatten = false;
+    for attr in attrs.iter().filter(|a| a.path().is_ident("from")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("flatten") {
+                flatten = true;
+                Ok(())
+            } else {
+                Err(meta.error("Unknown from option"))
+            }
+        })?;
+    }
+    Ok(flatten)
+}
+
+/// Check whether an enum is marked with `#[from(register)]`, so that other enums can flatten it
+fn registered(attrs: &[Attribute]) -> Result<bool> {
+    let mut register = false;
+    for attr in attrs.iter().filter(|a| a.path().is_ident("from")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("register") {
+                register = true;
+                Ok(())
+            } else {
+                Err(meta.error("Unknown from option"))
+            }
+        })?;
+    }
+    Ok(register)
+}
+
+/// Register an enum deriving `From` with `#[from(register)]` so that other enums can flatten it. A
+/// macro sharing the enum's name passes its definition, variant types included, to
+/// [flatten_variant], in the same way as `#[forwardable]` does for traits.
+pub(crate) fn register(item: &ItemEnum, krate: &Path) -> Result<TokenStream> {
+    if !registered(&item.attrs)? {
+        return Ok(TokenStream::new());
+    }
+    let item_ident = &item.ident;
+
+    // only the shape of the variants is needed to flatten the enum
+    let mut def = item.clone();
+    def.attrs.clear();
+    for variant in &mut def.variants {
+        variant.attrs.retain(|a| a.path().is_ident("enum_forward"));
+        variant.discriminant = None;
+        for field in &mut variant.fields {
+            field.attrs.clear();
+        }
+    }
+
+    let rules = quote! {
+        ($($input:tt)*) => {
+            #krate::__flatten_variant!{ { #def } $($input)* }
+        };
+    };
+    Ok(item_macro(item_ident, &item.vis, item, rules))
+}
+
+/// Call the macro registered by the enum in a `#[from(flatten)]` variant to implement `derive`
+/// (`From` or `TryInto`) for its variant types. The enum needs to be marked `#[from(register)]`
+pub(crate) fn flatten_call(derive: &str, item: &ItemEnum, variant: &Variant, inner_ty: &Type) -> Result<TokenStream> {
+    let Type::Path(ty_path) = inner_ty else {
+        return Err(Error::Other(inner_ty.span(), "Only variants containing an enum deriving `From` can be flattened".into()));
+    };
+    let mut macro_path = ty_path.path.clone();
+    macro_path.segments.last_mut().unwrap().arguments = Default::default();
+
+    let derive = format_ident!("{}", derive);
+    let var_ident = &variant.ident;
+    Ok(quote!(#macro_path!{ #derive #var_ident; #inner_ty; #item }))
+}
+
+struct FlattenInput {
+    def: ItemEnum,
+    derive: Ident,
+    var_ident: Ident,
+    inner_ty: Type,
+    item: ItemEnum,
+}
+
+impl Parse for FlattenInput {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let content;
+        syn::braced!(content in input);
+        let def = content.parse()?;
+        let derive = input.parse()?;
+        let var_ident = input.parse()?;
+        input.parse::<Token!(;)>()?;
+        let inner_ty = input.parse()?;
+        input.parse::<Token!(;)>()?;
+        let item = input.parse()?;
+
+        Ok(FlattenInput { def, derive, var_ident, inner_ty, item })
+    }
+}
+
+pub fn flatten_variant(input: TokenStream) -> Result<TokenStream> {
+    let FlattenInput { def, derive, var_ident, inner_ty, item } = parse2(input)?;
+    let item_ident = &item.ident;
+    let (impl_generics, ty_generics, where_clause) = item.generics.split_for_impl();
+
+    let Some(VariantInfo { pattern: outer_pattern, .. }) = variant_patterns(&item)
+        .filter_map(|v| v.ok())
+        .find(|v| v.variant.ident == var_ident) else {
+        return Err(Error::Other(var_ident.span(), "Flattened variant not found".into()));
+    };
+
+    // the inner enum is named by the path written in the outer enum, since the path
+    // in its own definition may not resolve here
+    let Type::Path(inner_path) = &inner_ty else {
+        return Err(Error::Other(inner_ty.span(), "Only variants containing an enum deriving `From` can be flattened".into()));
+    };
+    let mut inner_path = inner_path.path.clone();
+    inner_path.segments.last_mut().unwrap().arguments = Default::default();
+
+    // the variant types are written as the inner enum declares them, with its type parameters
+    // replaced by the arguments the outer enum gives them
+    let outer_params = type_params(&item.generics);
+    let inner_variants = def.variants.iter().filter_map(|variant| {
+        if skipped(&variant.attrs).unwrap_or(true) {
+            return None;
+        }
+        let (pattern, ty) = nested_pattern(&inner_path, variant)?;
+        Some((variant, pattern, substitute_params(ty, &def.generics, &inner_ty)))
+    }).collect_vec();
+
+    let mut output = TokenStream::new();
+
+    for (variant, pattern, ty) in &inner_variants {
+        // a type parameter `T` of the outer enum would conflict with the blanket impls in core
+        if matches!(ty, Type::Path(p) if p.qself.is_none() && p.path.get_ident().is_some_and(|i| outer_params.contains(i))) {
+            continue;
+        }
+
+        if derive == "From" {
+            output.extend(quote! {
+                impl #impl_generics ::core::convert::From<#ty> for #item_ident #ty_generics #where_clause {
+                    fn from(value : #ty) -> Self {
+                        let value = #pattern;
+                        #outer_pattern
+                    }
+                }
+            });
+            continue;
+        }
+
+        let try_ty_name = display_tokens(ty);
+        let inner_arms = inner_variants.iter().map(|(other, pattern, ..)| {
+            if other.ident == variant.ident {
+                quote!(#pattern => ::core::result::Result::Ok(value))
+            } else {
+                let msg = format!("Cannot convert {}::{} to {}", def.ident, other.ident, try_ty_name);
+                quote!(#pattern => ::core::result::Result::Err(#msg))
+            }
+        });
+        let inner_fallback = fallback_arm(inner_variants.len(), def.variants.len()).map(|_| {
+            let msg = format!("Cannot convert {} to {}", def.ident, try_ty_name);
+            quote!(_ => ::core::result::Result::Err(#msg))
+        });
+        let outer_arms = item.variants.iter().filter(|v| v.ident != var_ident).map(|other| {
+            let other_ident = &other.ident;
+            let msg = format!("Cannot convert {}::{} to {}", item_ident, other_ident, try_ty_name);
+            quote!(#item_ident::#other_ident { .. } => ::core::result::Result::Err(#msg))
+        });
+
+        output.extend(quote! {
+            impl #impl_generics ::core::convert::TryInto<#ty> for #item_ident #ty_generics #where_clause {
+                type Error = &'static str;
+
+                fn try_into(self) -> ::core::result::Result<#ty, <Self as ::core::convert::TryInto<#ty>>::Error> {
+                    match self {
+                        #outer_pattern => match value {
+                            #(#inner_arms,)*
+                            #inner_fallback
+                        },
+                        #(#outer_arms,)*
+                    }
+                }
+            }
+        });
+    }
+
+    Ok(output)
+}
+
+/// A pattern binding `value` to the inner value of a variant of the enum at `path`, and the
+/// inner value's type
+fn nested_pattern<'a>(path: &Path, variant: &'a Variant) -> Option<(TokenStream, &'a Type)> {
+    let var_ident = &variant.ident;
+    match &variant.fields {
+        Fields::Named(ns) if ns.named.len() == 1 => {
+            let field = &ns.named[0];
+            let field_ident = &field.ident;
+            Some((quote!(#path::#var_ident{#field_ident : value}), &field.ty))
+        }
+        Fields::Unnamed(us) if us.unnamed.len() == 1 => {
+            Some((quote!(#path::#var_ident(value)), &us.unnamed[0].ty))
+        }
+        _ => None,
+    }
+}
+
+/// Replace the generic parameters of the inner enum in one of its variant types with the arguments
+/// the outer enum gives them, so the type is named as the outer enum does
+fn substitute_params(ty: &Type, generics: &Generics, inner_ty: &Type) -> Type {
+    struct Substitute(Vec<(Ident, Type)>, Vec<(Lifetime, Lifetime)>);
+
+    impl VisitMut for Substitute {
+        fn visit_lifetime_mut(&mut self, lifetime: &mut Lifetime) {
+            if let Some((_, arg)) = self.1.iter().find(|(param, _)| param == lifetime) {
+                *lifetime = arg.clone();
+            }
+        }
+
+        fn visit_type_mut(&mut self, ty: &mut Type) {
+            if let Type::Path(p) = ty {
+                if let Some((_, arg)) = p.qself.is_none().then(|| p.path.get_ident()).flatten()
+                    .and_then(|i| self.0.iter().find(|(param, _)| param == i)) {
+                    *ty = arg.clone();
+                    return;
+                }
+            }
+            visit_mut::visit_type_mut(self, ty);
+        }
+    }
+
+    let args = match inner_ty {
+        Type::Path(p) => match &p.path.segments.last().unwrap().arguments {
+            PathArguments::AngleBracketed(args) => args.args.iter().collect_vec(),
+            _ => vec![],
+        },
+        _ => vec![],
+    };
+    // lifetimes can be left out of the arguments, so match the two up separately
+    let params = generics.params.iter().filter_map(|p| match p {
+        GenericParam::Type(t) => Some(t.ident.clone()),
+        _ => None,
+    });
+    let types = args.iter().filter_map(|a| match a {
+        GenericArgument::Type(t) => Some(t.clone()),
+        _ => None,
+    });
+    let lifetime_params = generics.lifetimes().map(|l| l.lifetime.clone());
+    let lifetimes = args.iter().filter_map(|a| match a {
+        GenericArgument::Lifetime(l) => Some(l.clone()),
+        _ => None,
+    });
+
+    let mut ty = ty.clone();
+    Substitute(params.zip(types).collect(), lifetime_params.zip(lifetimes).collect()).visit_type_mut(&mut ty);
+    ty
+}
@@ -4,60 +4,209 @@
 
 use std::collections::HashSet;
 use itertools::Itertools;
-use proc_macro2::{Ident, Span, TokenStream};
-use quote::{format_ident, quote, ToTokens};
-use syn::{Attribute, FnArg, GenericParam, Generics, ItemEnum, Lifetime, LifetimeParam, parse2, Pat, PatIdent, Signature, Token, TraitBound, Type, TypeParam, TypeParamBound, TypePath, Visibility, WherePredicate, PredicateType, WhereClause, ReturnType, TypeTuple};
+use proc_macro2::{Span, TokenStream};
+use quote::{quote, ToTokens};
+use syn::{Attribute, FnArg, GenericParam, Lifetime, LifetimeParam, parse2, parse_quote, Pat, Path, PatIdent, ReturnType, Signature, Token, TraitBound, Type, TypeParam, Visibility};
 use syn::parse::{Parse, ParseStream};
 use syn::punctuated::Punctuated;
 use syn::spanned::Spanned;
 use syn::token::Brace;
 
 
-use crate::common::{variant_patterns, VariantInfo, lifetimeify};
-use crate::error::{Error, Result};
+use crate::common::{display_tokens, ForwardArm, ForwardItem, fresh_ident, item_options, lifetimeify, MethodHints, normalize_type, token_idents};
+use crate::error::{Error, Errors, Result};
+use crate::forward_trait::{contains_impl, forward_overrides, forward_trait_impls, ForwardOverride};
+use crate::inherent::{forward_flattened, impl_take};
+use crate::ops::{mismatch, Mismatch};
+
+/// An item with `#[derive(Forward)]`, with its options and the arms of a match on it parsed once
+/// for everything the derive generates
+pub(crate) struct Forwarding<'a> {
+    pub item: &'a ForwardItem,
+    pub krate: Path,
+    pub hints: MethodHints,
+    /// what binary operators do with operands of different variants
+    pub mismatch: Mismatch,
+    pub arms: Vec<VariantArm<'a>>,
+    /// a catch-all arm for the variants that can't be forwarded, if there are any
+    pub fallback: Option<TokenStream>,
+}
+
+/// An arm of a match on an item with `#[derive(Forward)]`, with the options of its variant
+pub(crate) struct VariantArm<'a> {
+    pub arm: ForwardArm<'a>,
+    /// whether the variant is marked with `#[forward(flatten)]`
+    pub flatten: bool,
+    /// the entries of the variant's `#[forward_override(...)]` attributes
+    pub overrides: Vec<ForwardOverride>,
+}
+
+impl<'a> Forwarding<'a> {
+    /// Parse the options and arms of an item, reporting any errors to `errors`
+    pub fn parse(item: &'a ForwardItem, errors: &mut Errors) -> Self {
+        let options = errors.check(item_options(item.attrs())).unwrap_or_default();
+        let mismatch = errors.check(mismatch(item.attrs())).unwrap_or(Mismatch::Panic);
+        let arms = item.arms(errors);
+        let fallback = item.fallback_arm(&arms);
+        let arms = arms.into_iter().map(|arm| {
+            let attrs = arm.variant.map_or(&[][..], |v| &v.attrs);
+            let flatten = errors.check(forward_flattened(attrs)).unwrap_or(false);
+            let overrides = errors.check(forward_overrides(attrs)).unwrap_or_default();
+            VariantArm { arm, flatten, overrides }
+        }).collect();
+        Forwarding { item, krate: options.krate, hints: options.hints, mismatch, arms, fallback }
+    }
+
+    /// Parse an item again in the macros the derive's output calls back into. The derive has
+    /// already reported any errors, so they're dropped
+    pub fn reparse(item: &'a ForwardItem) -> Self {
+        Self::parse(item, &mut Errors::default())
+    }
+
+    /// The types of the inner values, without duplicates
+    pub fn inner_tys(&self) -> Vec<&'a Type> {
+        self.arms.iter().map(|a| a.arm.inner_ty).unique().collect()
+    }
+}
 
 pub fn forwarding2(item: TokenStream) -> Result<TokenStream> {
     let mut output = TokenStream::new();
 
-    let item = parse2::<ItemEnum>(item)?;
-    let (impl_generics, ty_generics, where_clause) = item.generics.split_for_impl();
-    let item_ident = item.ident.clone();
+    let item = parse2::<ForwardItem>(item)?;
+    let item_ident = item.ident().clone();
+    let mut errors = Errors::default();
+    let forwarding = Forwarding::parse(&item, &mut errors);
+    let krate = &forwarding.krate;
+    let (_, ty_generics, _) = item.generics().split_for_impl();
+
+    // the generic parameters can't clash with anything used in the item
+    let mut taken = token_idents(item.to_token_stream());
+    let input_ty = fresh_ident("I", &mut taken);
+    let visitor_ty = fresh_ident("V", &mut taken);
+    let output_ty = fresh_ident("R", &mut taken);
+
+    let fallback = &forwarding.fallback;
+    let inner_tys = forwarding.inner_tys();
+    let patterns = forwarding.arms.iter().map(|a| &a.arm.pattern).collect_vec();
+
+    // without any arms to constrain the output type the impls would only add more errors
+    if forwarding.arms.is_empty() {
+        output.extend(forward_trait_impls(&forwarding, &mut errors));
+        return Ok(errors.finish(output));
+    }
 
-    let mut impl_generics = parse2::<Generics>(impl_generics.to_token_stream())?;
+    // the input can be passed by reference, mutable reference or by value
+    let input_modes = [
+        (quote!(Forward), quote!(forward), quote!(&#input_ty)),
+        (quote!(ForwardMut), quote!(forward_mut), quote!(&mut #input_ty)),
+        (quote!(ForwardOwned), quote!(forward_owned), quote!(#input_ty)),
+    ];
+    for (forward_trait, method, input) in input_modes {
+        let mut generics = item.generics().clone();
+        generics.params.push(GenericParam::Type(TypeParam::from(input_ty.clone())));
+        generics.params.push(GenericParam::Type(TypeParam::from(output_ty.clone())));
+        let where_clause = generics.make_where_clause();
+        for ty in &inner_tys {
+            where_clause.predicates.push(parse2(quote!(#ty : #krate::#forward_trait<#input_ty, Output=#output_ty>))?);
+        }
+        let (impl_generics, _, where_clause) = generics.split_for_impl();
 
-    let input_ty = Ident::new("I", Span::call_site());
-    let output_ty = Ident::new("R", Span::call_site());
+        output.extend(quote! {
+            impl #impl_generics #krate::#forward_trait<#input_ty> for #item_ident #ty_generics #where_clause {
+                type Output = #output_ty;
 
-    impl_generics.params.push(GenericParam::Type(TypeParam::from(input_ty.clone())));
-    impl_generics.params.push(GenericParam::Type(TypeParam::from(output_ty.clone())));
+                fn #method(&self, input : #input) -> #output_ty {
+                    match self {
+                        #(#patterns => {#krate::#forward_trait::#method(value, input)},)*
+                        #fallback
+                    }
+                }
+            }
+        });
+    }
 
-    let types = variant_patterns(&item).map(|v| Ok((v?.inner_ty).clone())).collect::<Result<Vec<_>>>()?;
-    let additional_wheres = types.iter().unique().map(|ty| {
-        Ok(quote!(#ty : enum_forward::Forward<#input_ty, Output=#output_ty>))
-    }).collect::<Result<Vec<_>>>()?;
-    let where_clause = match where_clause {
-        None => { quote!(where #(#additional_wheres),*) }
-        Some(w) => { quote!(#w, #(#additional_wheres),*) }
-    };
+    // the same dispatch, but with the visitor implementing a trait for each inner type instead
+    let dispatch_modes = [
+        (quote!(Visitor), quote!(visit), quote!(Dispatch), quote!(dispatch), quote!(&self)),
+        (quote!(VisitorMut), quote!(visit_mut), quote!(DispatchMut), quote!(dispatch_mut), quote!(&mut self)),
+    ];
+    for (visitor_trait, visit, dispatch_trait, dispatch, receiver) in dispatch_modes {
+        let mut generics = item.generics().clone();
+        generics.params.push(GenericParam::Type(TypeParam::from(visitor_ty.clone())));
+        generics.params.push(GenericParam::Type(TypeParam::from(output_ty.clone())));
+        let where_clause = generics.make_where_clause();
+        for ty in &inner_tys {
+            where_clause.predicates.push(parse2(quote!(#visitor_ty : #krate::#visitor_trait<#ty, Output=#output_ty>))?);
+        }
+        let (impl_generics, _, where_clause) = generics.split_for_impl();
 
-    let arms = variant_patterns(&item).map(|v| {
-        let VariantInfo { pattern, .. } = v?;
-        Ok(quote!(#pattern => {enum_forward::Forward::forward(value, input)}))
-    }).collect::<Result<Vec<_>>>()?;
+        output.extend(quote! {
+            impl #impl_generics #krate::#dispatch_trait<#visitor_ty> for #item_ident #ty_generics #where_clause {
+                type Output = #output_ty;
 
-    output.extend(quote! {
-        impl<#input_ty,#output_ty> enum_forward::Forward<#input_ty> for #item_ident #ty_generics #where_clause {
-            type Output = #output_ty;
+                fn #dispatch(#receiver, visitor : #visitor_ty) -> #output_ty {
+                    match self {
+                        #(#patterns => {#krate::#visitor_trait::#visit(visitor, value)},)*
+                        #fallback
+                    }
+                }
+            }
+        });
+    }
 
-            fn forward(&self, input : &#input_ty) -> #output_ty {
-                return match self {
-                    #(#arms),*
+    // transforming the value in place needs to rebuild the variant, which a struct's pattern
+    // can't do when it has other fields. Structs also have no variant to name
+    if let ForwardItem::Enum(item_enum) = &item {
+        let mapper_ty = fresh_ident("M", &mut taken);
+        let error_ty = fresh_ident("E", &mut taken);
+
+        let mut generics = item.generics().clone();
+        generics.params.push(GenericParam::Type(TypeParam::from(mapper_ty.clone())));
+        let where_clause = generics.make_where_clause();
+        for ty in &inner_tys {
+            where_clause.predicates.push(parse2(quote!(#mapper_ty : #krate::Mapper<#ty>))?);
+        }
+        let (impl_generics, _, where_clause) = generics.split_for_impl();
+
+        output.extend(quote! {
+            impl #impl_generics #krate::MapInner<#mapper_ty> for #item_ident #ty_generics #where_clause {
+                fn map_inner(self, mapper : #mapper_ty) -> Self {
+                    match self {
+                        #(#patterns => { let value = #krate::Mapper::map(mapper, value); #patterns },)*
+                        #fallback
+                    }
                 }
             }
+        });
+
+        let mut generics = item.generics().clone();
+        generics.params.push(GenericParam::Type(TypeParam::from(mapper_ty.clone())));
+        generics.params.push(GenericParam::Type(TypeParam::from(error_ty.clone())));
+        let where_clause = generics.make_where_clause();
+        for ty in &inner_tys {
+            where_clause.predicates.push(parse2(quote!(#mapper_ty : #krate::TryMapper<#ty, Error=#error_ty>))?);
         }
-    });
+        let (impl_generics, _, where_clause) = generics.split_for_impl();
+
+        output.extend(quote! {
+            impl #impl_generics #krate::TryMapInner<#mapper_ty> for #item_ident #ty_generics #where_clause {
+                type Error = #error_ty;
+
+                fn try_map_inner(self, mapper : #mapper_ty) -> ::core::result::Result<Self, #error_ty> {
+                    ::core::result::Result::Ok(match self {
+                        #(#patterns => { let value = #krate::TryMapper::try_map(mapper, value)?; #patterns },)*
+                        #fallback
+                    })
+                }
+            }
+        });
 
-    Ok(output)
+        output.extend(errors.check(impl_take(item_enum, &forwarding)));
+    }
+
+    output.extend(forward_trait_impls(&forwarding, &mut errors));
+
+    Ok(errors.finish(forwarding.hints.apply(output)?))
 }
 
 struct InputFn {
@@ -87,8 +236,8 @@ impl Parse for InputFn {
 struct InputAttr {
     pub pat: Option<Pat>,
     pub ty: Type,
-    pub as_token: Token!(as),
     pub traits: Punctuated<TraitBound, Token!(+)>,
+    pub krate: Path,
 }
 
 impl Parse for InputAttr {
@@ -102,155 +251,225 @@ impl Parse for InputAttr {
         };
 
         let ty: Type = input.parse()?;
-        let as_token: Token!(as) = input.parse()?;
-        let traits = Punctuated::<TraitBound, Token!(+)>::parse_terminated(input)?;
+        input.parse::<Token!(as)>()?;
+        let traits = Punctuated::<TraitBound, Token!(+)>::parse_separated_nonempty(input)?;
+
+        let mut krate = parse_quote!(::enum_forward);
+        if input.parse::<Option<Token!(,)>>()?.is_some() && !input.is_empty() {
+            input.parse::<Token!(crate)>()?;
+            input.parse::<Token!(=)>()?;
+            krate = input.parse()?;
+        }
 
-        Ok(InputAttr { pat, ty, as_token, traits })
+        Ok(InputAttr { pat, ty, traits, krate })
     }
 }
 
 pub fn forward_to(attr: TokenStream, item: TokenStream) -> Result<TokenStream> {
     let attr: InputAttr = parse2(attr)?;
-    let item: InputFn = parse2(item)?;
-    let item_attrs = item.attrs;
-    let item_vis = item.vis;
-    let item_sig = item.sig;
-
-    let result_ty = match item_sig.output.clone() {
-        ReturnType::Default => { Type::Tuple(TypeTuple { paren_token: Default::default(), elems: Default::default() }) }
-        ReturnType::Type(_, bt) => { *bt.clone() }
+    let InputFn { attrs, vis, sig, body } = parse2(item)?;
+    let krate = &attr.krate;
+    let method = &sig.ident;
+
+    let (target, args) = forward_to_args(&attr, &sig)?;
+    // `&mut self` or a `&mut` argument needs mutable access to the inner value, anything else
+    // only borrows it
+    let mutable = match (sig.receiver(), &attr.pat) {
+        (Some(receiver), _) => receiver.reference.is_some() && receiver.mutability.is_some(),
+        (None, _) => matches!(&attr.ty, Type::Reference(r) if r.mutability.is_some()),
+    };
+    let (receiver, visitor_trait, visit, dispatch_trait, dispatch) = if mutable {
+        (quote!(&mut self), quote!(VisitorMut), quote!(visit_mut), quote!(DispatchMut), quote!(dispatch_mut))
+    } else {
+        (quote!(&self), quote!(Visitor), quote!(visit), quote!(Dispatch), quote!(dispatch))
     };
 
-    let ident = item_sig.clone().ident;
+    let mut taken = token_idents(sig.to_token_stream());
+    taken.extend(token_idents(body.clone().unwrap_or_default()));
+    let camel = upper_camel_case(&method.to_string());
+    let visitor = fresh_ident(&format!("{}Visitor", camel), &mut taken);
+    let variant_trait = fresh_ident(&format!("{}Variant", camel), &mut taken);
+    let variant_method = fresh_ident("call", &mut taken);
+    let blanket_ty = fresh_ident("B", &mut taken);
+    let blanket_lt = Lifetime::new(&format!("'{}", fresh_ident("_blanket", &mut taken)), Span::call_site());
+    let value_ty = if mutable { quote!(&mut #blanket_ty) } else { quote!(&#blanket_ty) };
+
+    // none of the items declared in the function can use its generics, so they each declare
+    // their own copy of them
+    let (_, fn_ty_generics, fn_where_clause) = sig.generics.split_for_impl();
+    let mut generics = sig.generics.clone();
+    generics.params.insert(0, GenericParam::Lifetime(LifetimeParam::new(blanket_lt.clone())));
+    let (_, ty_generics, where_clause) = generics.split_for_impl();
+
+    let idents = args.iter().map(|(pat, _)| &pat.ident).collect_vec();
+    let mut lifetimes = HashSet::new();
+    let field_tys = args.iter().map(|(_, ty)| lifetimeify((*ty).clone(), &blanket_lt, &mut lifetimes)).collect_vec();
+    let params = args.iter().map(|(pat, ty)| quote!(#pat : #ty)).collect_vec();
+    let phantom_lifetimes = generics.lifetimes().map(|l| &l.lifetime).collect_vec();
+    let phantom_types = generics.type_params().map(|t| &t.ident).collect_vec();
+
+    let output_ty = match &sig.output {
+        ReturnType::Default => quote!(()),
+        ReturnType::Type(_, ty) => ty.to_token_stream(),
+    };
 
-    let mut inner = TokenStream::new();
+    // each variant either runs the function body with `self` bound to it, or calls the method
+    // of the same name on it
+    let call = match &body {
+        Some(body) => body.clone(),
+        None => quote!({ self.#method(#(#idents),*) }),
+    };
 
-    let mut receiver: Option<(Type, Pat)> = None;
-    let mut args: Vec<(Type, Pat)> = vec![];
+    let traits = &attr.traits;
+    let mut variant_generics = sig.generics.clone();
+    variant_generics.params.push(GenericParam::Type(TypeParam::from(blanket_ty.clone())));
+    variant_generics.make_where_clause().predicates.push(parse2(quote!(#blanket_ty : #traits))?);
+    let (variant_impl_generics, _, variant_where_clause) = variant_generics.split_for_impl();
+
+    let mut visitor_generics = generics.clone();
+    visitor_generics.params.push(GenericParam::Type(TypeParam::from(blanket_ty.clone())));
+    visitor_generics.make_where_clause().predicates.push(parse2(quote!(#blanket_ty : #variant_trait #fn_ty_generics))?);
+    let (visitor_impl_generics, _, visitor_where_clause) = visitor_generics.split_for_impl();
+    let fn_generics = &sig.generics;
+
+    Ok(quote! {
+        #(#attrs)*
+        #vis #sig {
+            trait #variant_trait #fn_generics #fn_where_clause {
+                fn #variant_method(#receiver, #(#params),*) -> #output_ty;
+            }
 
-    let caller_ty: Type = parse2(quote!(Caller))?;
+            impl #variant_impl_generics #variant_trait #fn_ty_generics for #blanket_ty #variant_where_clause {
+                #[inline]
+                fn #variant_method(#receiver, #(#params),*) -> #output_ty #call
+            }
 
-    let self_: PatIdent = PatIdent {
-        attrs: vec![],
-        by_ref: None,
-        mutability: None,
-        ident: Ident::new("_self", Span::call_site()),
-        subpat: None,
-    };
+            struct #visitor #generics #where_clause {
+                #(#idents : #field_tys,)*
+                _phantom : ::core::marker::PhantomData<(#(&#phantom_lifetimes (),)* #(fn() -> #phantom_types,)*)>,
+            }
 
-    for input in item_sig.clone().inputs.iter().rev() {
-        match input {
-            FnArg::Typed(typed) => {
-                let pat = *typed.pat.clone();
+            impl #visitor_impl_generics #krate::#visitor_trait<#blanket_ty> for #visitor #ty_generics #visitor_where_clause {
+                type Output = #output_ty;
 
-                match (Some(pat.clone()), *typed.ty.clone()) {
-                    (p, t) if p == attr.pat.clone() && t == attr.ty.clone() => {
-                        receiver = Some((t.clone(), pat.clone()))
-                    }
-                    (p, t) if p == attr.pat.clone() => {
-                        return Err(Error::MismatchedArgType(t.span()));
-                    }
-                    (p, t) if t == attr.ty.clone() => {
-                        return Err(Error::MismatchedArgType(p.span()));
-                    }
-                    (p, t) => {
-                        args.insert(0, (t.clone(), pat.clone()))
-                    }
+                #[inline]
+                fn #visit(self, value : #value_ty) -> #output_ty {
+                    #variant_trait::#variant_method(value, #(self.#idents),*)
                 }
             }
-            FnArg::Receiver(rec) => {
-                if receiver.is_none() {
-                    receiver = Some((attr.ty.clone(), Pat::Ident(self_.clone())));
-                } else {
-                    args.insert(0, (*rec.ty.clone(), Pat::Ident(self_.clone())))
-                }
-            }
-        }
-    }
-
-    let blanket_lt = Lifetime::new("'_blanket", Span::call_site());
-    let mut lifetimes = HashSet::<Lifetime>::from([blanket_lt.clone()]);
 
-    for (ref mut ty, _) in &mut args {
-        (*ty) = lifetimeify(ty.clone(), &blanket_lt, &mut lifetimes);
-    }
+            use #krate::#dispatch_trait as _;
+            (#target).#dispatch(#visitor {
+                #(#idents,)*
+                _phantom : ::core::marker::PhantomData,
+            })
+        }
+    })
+}
 
-    let mut struct_generics = item_sig.clone().generics;
-    struct_generics.params.extend([blanket_lt.clone()].iter().map(|lt| GenericParam::Lifetime(LifetimeParam {
-        attrs: vec![],
-        lifetime: lt.clone(),
-        colon_token: None,
-        bounds: Default::default(),
-    })));
-
-    let (impl_generics, ty_generics, where_clause) = struct_generics.split_for_impl();
-    let struct_ident = format_ident!("{}Visitor", ident.clone());
-    let struct_items = &args.iter().map(|(ty, pat)| {
-        quote!(#pat : #ty)
+/// Find the value a `forward_to` function forwards, and the arguments passed along with it
+fn forward_to_args<'a>(attr: &InputAttr, sig: &'a Signature) -> Result<(TokenStream, Vec<(&'a PatIdent, &'a Type)>)> {
+    let method = &sig.ident;
+    let attr_ty = normalize_type(&attr.ty);
+    let typed = sig.inputs.iter().filter_map(|i| match i {
+        FnArg::Typed(t) => Some(t),
+        FnArg::Receiver(_) => None,
     }).collect_vec();
-
-    inner.extend(
-        quote! {
-            struct #struct_ident #ty_generics #where_clause {
-                #(#struct_items),*,
-                _phantom : std::marker::PhantomData<&#blanket_lt i32>
+    let same_ty = typed.iter().filter(|t| normalize_type(&t.ty) == attr_ty).collect_vec();
+
+    let target = match (sig.receiver(), &attr.pat) {
+        (Some(receiver), None) => {
+            if receiver.colon_token.is_some() {
+                return Err(Error::Syn(syn::Error::new_spanned(receiver, format!(
+                    "`{}` has a typed receiver, which can't be forwarded. Use `&self`, `&mut self` or `self`",
+                    method))));
             }
-
+            None
         }
-    );
-
-    inner.extend(impl_forward_variants(struct_generics.clone(), Type::Verbatim(struct_ident.clone().into_token_stream()), result_ty.clone(), attr.traits.clone()));
-
-
-    if item.body.is_some() {
-        unimplemented!()
-    }
-
-    let output = quote! {
-        #(#item_attrs)*
-        #item_vis #item_sig {
-            #inner
+        (Some(receiver), Some(pat)) => {
+            return Err(Error::Other(pat.span(), format!(
+                "`{}` has a `self` receiver, which is always the value forwarded. Try {}",
+                method, suggest_attr(None, &attr.ty, attr)))
+                .note(receiver, "the receiver is declared here"));
+        }
+        (None, None) => {
+            let err = match same_ty.as_slice() {
+                [arg] => Error::Syn(syn::Error::new_spanned(&attr.ty, format!(
+                    "`{}` has no `self` receiver, so the argument to forward must be named. Try {}",
+                    method, suggest_attr(Some(&arg.pat), &arg.ty, attr)))),
+                _ => Error::Syn(syn::Error::new_spanned(&attr.ty, format!(
+                    "`{}` has no `self` receiver, so the argument to forward must be named, \
+                    like `#[forward_to(value: {} as ...)]`", method, display_tokens(&attr.ty)))),
+            };
+            return Err(same_ty.iter().fold(err, |err, arg| err.note(arg, "this argument has the forwarded type")));
+        }
+        (None, Some(pat)) => {
+            let named = typed.iter().filter(|t| *t.pat == *pat).collect_vec();
+            match named.as_slice() {
+                [arg] if normalize_type(&arg.ty) == attr_ty => Some(&arg.pat),
+                [arg] => {
+                    return Err(Error::MismatchedArgType {
+                        attr_ty: Box::new(attr.ty.clone()),
+                        arg_ty: arg.ty.clone(),
+                        suggestion: suggest_attr(Some(&arg.pat), &arg.ty, attr),
+                    });
+                }
+                [] => {
+                    let err = match same_ty.as_slice() {
+                        [arg] => Error::Other(pat.span(), format!(
+                            "`{}` has no argument `{}`. Try {}",
+                            method, display_tokens(pat), suggest_attr(Some(&arg.pat), &arg.ty, attr))),
+                        _ => Error::Other(pat.span(), format!(
+                            "`{}` has no argument `{}` to forward", method, display_tokens(pat))),
+                    };
+                    return Err(same_ty.iter().fold(err, |err, arg| err.note(arg, "this argument has the forwarded type")));
+                }
+                [_, ..] => {
+                    let err = Error::Other(pat.span(), format!(
+                        "More than one argument of `{}` matches `{}`. Give the argument to forward a unique name",
+                        method, display_tokens(pat)));
+                    return Err(named.iter().fold(err, |err, arg| err.note(arg, "this argument matches")));
+                }
+            }
         }
     };
 
-
-    Ok(output)
-}
-
-fn impl_forward_variants(mut generics: Generics, input_ty: Type, result_ty: Type, traits_bounds: Punctuated<TraitBound, Token!(+)>)
-                         -> Result<TokenStream> {
-
-    let input_generics = generics.clone();
-    let (_, input_ty_generics, _) = input_generics.split_for_impl();
-
-
-    let blanket_ty = Ident::new("B", Span::call_site());
-    generics.params.extend([GenericParam::Type(TypeParam { attrs: vec![], ident: blanket_ty.clone(), colon_token: None, bounds: Default::default(), eq_token: None, default: None })].into_iter());
-    let mut where_clause = generics.where_clause.unwrap_or(WhereClause {
-        where_token: Default::default(),
-        predicates: Default::default(),
-    });
-    where_clause.predicates.extend(
-        [WherePredicate::Type(
-            PredicateType {
-                lifetimes: None,
-                bounded_ty: Type::Verbatim(blanket_ty.to_token_stream()),
-                colon_token: Default::default(),
-                bounds: traits_bounds.iter().map(
-                    |tb| { TypeParamBound::Trait(tb.clone()) }
-                ).collect(),
+    let target = match target {
+        None => sig.receiver().map(|r| r.self_token.to_token_stream()),
+        Some(pat) => match &**pat {
+            Pat::Ident(PatIdent { ident, subpat: None, .. }) => Some(ident.to_token_stream()),
+            other => {
+                return Err(Error::Other(other.span(), "The forwarded argument must be a plain identifier".into()));
             }
-        )].into_iter()
-    );
-    generics.where_clause = Some(where_clause);
-
-    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+        },
+    }.expect("forwarded value without a receiver or argument");
 
+    let args = typed.iter().filter(|t| attr.pat.as_ref() != Some(&*t.pat)).map(|t| {
+        if contains_impl(t.ty.to_token_stream()) {
+            return Err(Error::Other(t.ty.span(), "`impl Trait` arguments cannot be passed through `forward_to`. \
+            Use a generic parameter instead".into()));
+        }
+        match &*t.pat {
+            Pat::Ident(pat @ PatIdent { by_ref: None, subpat: None, .. }) => Ok((pat, &*t.ty)),
+            other => Err(Error::Other(other.span(), "Arguments passed through `forward_to` must be plain identifiers".into())),
+        }
+    }).collect::<Result<Vec<_>>>()?;
 
+    Ok((target, args))
+}
 
-    return Ok(quote! {
-        impl #impl_generics enum_forward::Forward<#input_ty::<'a, '_blanket>> for #blanket_ty #where_clause {
+/// Format a corrected `forward_to` attribute to suggest in an error
+fn suggest_attr(pat: Option<&Pat>, ty: &Type, attr: &InputAttr) -> String {
+    let traits = display_tokens(&attr.traits);
+    match pat {
+        Some(pat) => format!("`#[forward_to({}: {} as {})]`", display_tokens(pat), display_tokens(ty), traits),
+        None => format!("`#[forward_to({} as {})]`", display_tokens(ty), traits),
+    }
+}
 
-        }
-    });
-}
\ No newline at end of file
+fn upper_camel_case(s: &str) -> String {
+    s.split('_').filter(|w| !w.is_empty()).map(|w| {
+        let mut chars = w.chars();
+        chars.next().map(|c| c.to_uppercase().chain(chars).collect::<String>()).unwrap_or_default()
+    }).collect()
+}
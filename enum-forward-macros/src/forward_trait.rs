@@ -0,0 +1,512 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::collections::HashSet;
+use itertools::Itertools;
+use proc_macro2::{TokenStream, TokenTree};
+use quote::{format_ident, quote, ToTokens};
+use syn::{Attribute, ConstParam, FnArg, GenericParam, Generics, Ident, ItemTrait, Lifetime, LifetimeParam, parenthesized, parse2, parse_quote, Pat, PatIdent, Path, Token, TraitItem, TypeParam};
+use syn::parse::{Parse, Parser, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::spanned::Spanned;
+
+use crate::builtin::{builtin_trait, shadowable};
+use crate::common::{ForwardItem, fresh_ident, item_macro, replace_ident, replace_lifetime, token_idents};
+use crate::error::{Error, Errors, Result};
+use crate::forward::{Forwarding, VariantArm};
+use crate::inherent::{impl_any, impl_dyn, impl_method, is_any, is_method};
+use crate::ops::{impl_operator, is_operator};
+
+/// A `Trait::method = path::to_fn` entry in a `#[forward_override(...)]` variant attribute
+pub(crate) struct ForwardOverride {
+    pub method: Path,
+    pub func: Path,
+}
+
+impl Parse for ForwardOverride {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let method: Path = input.parse()?;
+        input.parse::<Token!(=)>()?;
+        let func: Path = input.parse()?;
+
+        if method.segments.len() < 2 {
+            return Err(syn::Error::new(method.span(), "Expected a method path like `Trait::method`"));
+        }
+
+        Ok(ForwardOverride { method, func })
+    }
+}
+
+impl ForwardOverride {
+    fn trait_ident(&self) -> &syn::Ident {
+        &self.method.segments[self.method.segments.len() - 2].ident
+    }
+
+    fn method_ident(&self) -> &syn::Ident {
+        &self.method.segments.last().unwrap().ident
+    }
+}
+
+/// An entry in a `#[forward(...)]` attribute
+pub(crate) enum ForwardEntry {
+    /// `Trait`, implemented by forwarding to the inner value
+    Trait(Path),
+    /// `dyn(Trait)`, converting the inner value to a trait object
+    Dyn(Path),
+    /// `kind` or `replace_inner`, generating the inherent method of that name
+    Method(Ident),
+}
+
+impl Parse for ForwardEntry {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        if input.peek(Token!(dyn)) {
+            input.parse::<Token!(dyn)>()?;
+            let content;
+            parenthesized!(content in input);
+            let path = content.parse()?;
+            content.parse::<Option<Token!(,)>>()?;
+            return Ok(ForwardEntry::Dyn(path));
+        }
+        let path: Path = input.parse()?;
+        match path.get_ident() {
+            Some(ident) if is_method(&path) => Ok(ForwardEntry::Method(ident.clone())),
+            _ => Ok(ForwardEntry::Trait(path)),
+        }
+    }
+}
+
+/// Collect the entries listed in every `#[forward(...)]` attribute
+pub(crate) fn forward_traits(attrs: &[Attribute]) -> Result<Vec<ForwardEntry>> {
+    let mut traits = vec![];
+    for attr in attrs.iter().filter(|a| a.path().is_ident("forward")) {
+        traits.extend(attr.parse_args_with(Punctuated::<ForwardEntry, Token!(,)>::parse_terminated)?);
+    }
+    Ok(traits)
+}
+
+/// Collect the overrides listed in every `#[forward_override(...)]` attribute on a variant
+pub(crate) fn forward_overrides(attrs: &[Attribute]) -> Result<Vec<ForwardOverride>> {
+    let mut overrides = vec![];
+    for attr in attrs.iter().filter(|a| a.path().is_ident("forward_override")) {
+        overrides.extend(attr.parse_args_with(Punctuated::<ForwardOverride, Token!(,)>::parse_terminated)?);
+    }
+    Ok(overrides)
+}
+
+/// Implement every trait listed in `#[forward(...)]` for the item. Traits from the standard
+/// library are implemented directly, other traits are implemented by calling the macro
+/// generated alongside them by `#[forwardable]`, which then calls back into [forward_trait].
+pub(crate) fn forward_trait_impls(forwarding: &Forwarding, errors: &mut Errors) -> TokenStream {
+    let item = forwarding.item;
+    let Some(entries) = errors.check(forward_traits(item.attrs())) else {
+        return TokenStream::new();
+    };
+    let traits = entries.iter().filter_map(|e| match e {
+        ForwardEntry::Trait(path) => Some(path),
+        ForwardEntry::Dyn(_) | ForwardEntry::Method(_) => None,
+    }).collect_vec();
+
+    for o in forwarding.arms.iter().flat_map(|a| &a.overrides) {
+        if !traits.iter().any(|t| t.segments.last().is_some_and(|s| &s.ident == o.trait_ident())) {
+            errors.push(Error::Other(o.method.span(), format!(
+                "`{}` is not forwarded by this enum. Add it to a `#[forward(...)]` attribute to override its methods",
+                o.trait_ident())));
+        }
+    }
+
+    let mut output = TokenStream::new();
+    let mut shadowed = Vec::new();
+    for entry in &entries {
+        let trait_path = match entry {
+            ForwardEntry::Trait(path) => path,
+            ForwardEntry::Dyn(path) => {
+                output.extend(errors.check(impl_dyn(forwarding, path)));
+                continue;
+            }
+            ForwardEntry::Method(method) => {
+                output.extend(errors.check(impl_method(forwarding, method)));
+                continue;
+            }
+        };
+        if is_any(trait_path) {
+            output.extend(errors.check(impl_any(forwarding)));
+            continue;
+        }
+        // the bare name calls the macro of a forwardable trait with that name if one is in
+        // scope, and otherwise the glob imported macro implementing the standard library trait
+        if shadowable(trait_path) {
+            let macro_ident = &trait_path.segments[0].ident;
+            output.extend(quote!(#macro_ident!{ #trait_path; #item }));
+            shadowed.push(macro_ident);
+            continue;
+        }
+        match impl_builtin(forwarding, trait_path, errors) {
+            Some(builtin) => output.extend(builtin),
+            None => output.extend(quote!(#trait_path!{ #trait_path; #item })),
+        }
+    }
+
+    // only a glob import in the item's own module loses to a forwardable trait imported by name,
+    // so glob import a hidden module holding just the fallbacks this item calls
+    if !shadowed.is_empty() {
+        let krate = &forwarding.krate;
+        let module = format_ident!("__forward_builtin_{}", item.ident());
+        output.extend(quote! {
+            #[doc(hidden)]
+            #[allow(non_snake_case, unused_imports)]
+            mod #module {
+                pub(super) use #krate::__builtin::{#(#shadowed),*};
+            }
+            #[allow(unused_imports)]
+            use self::#module::*;
+        });
+    }
+
+    output
+}
+
+/// Implement a standard library trait, or `None` if the path doesn't name one
+fn impl_builtin(forwarding: &Forwarding, trait_path: &Path, errors: &mut Errors) -> Option<TokenStream> {
+    if is_operator(trait_path) {
+        return Some(errors.check(impl_operator(forwarding, trait_path)).unwrap_or_default());
+    }
+    match errors.check(builtin_trait(trait_path)) {
+        Some(Some((path, def))) => Some(errors.check(impl_forward_trait(forwarding, &path, &def)).unwrap_or_default()),
+        Some(None) => None,
+        None => Some(TokenStream::new()),
+    }
+}
+
+pub fn forwardable(attr: TokenStream, item: TokenStream) -> Result<TokenStream> {
+    let mut krate: Path = parse_quote!(::enum_forward);
+    let parser = syn::meta::parser(|meta| {
+        if meta.path.is_ident("crate") {
+            krate = meta.value()?.parse()?;
+            Ok(())
+        } else {
+            Err(meta.error("Unknown forwardable option"))
+        }
+    });
+    Parser::parse2(parser, attr)?;
+
+    let item = parse2::<ItemTrait>(item)?;
+    let trait_ident = &item.ident;
+
+    // only the signatures are needed to forward the trait, so strip out
+    // default bodies and anything else that might confuse macro_rules
+    let mut def = item.clone();
+    def.attrs.clear();
+    for trait_item in &mut def.items {
+        match trait_item {
+            TraitItem::Fn(f) => {
+                f.default = None;
+                f.semi_token = Some(Default::default());
+                f.attrs.retain(|a| a.path().is_ident("cfg"));
+            }
+            TraitItem::Type(t) => {
+                t.default = None;
+                t.attrs.retain(|a| a.path().is_ident("cfg"));
+            }
+            _ => {}
+        }
+    }
+
+    let rules = quote! {
+        ($($item:tt)*) => {
+            #krate::__forward_trait!{ { #def } $($item)* }
+        };
+    };
+    let forward_macro = item_macro(trait_ident, &item.vis, &item, rules);
+
+    Ok(quote! {
+        #item
+
+        #forward_macro
+    })
+}
+
+struct ForwardTraitInput {
+    def: ItemTrait,
+    trait_path: Path,
+    item: ForwardItem,
+}
+
+impl Parse for ForwardTraitInput {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let content;
+        syn::braced!(content in input);
+        let def: ItemTrait = content.parse()?;
+        let trait_path: Path = input.parse()?;
+        input.parse::<Token!(;)>()?;
+        let item: ForwardItem = input.parse()?;
+
+        Ok(ForwardTraitInput { def, trait_path, item })
+    }
+}
+
+struct ForwardBuiltinInput {
+    trait_path: Path,
+    item: ForwardItem,
+}
+
+impl Parse for ForwardBuiltinInput {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let trait_path: Path = input.parse()?;
+        input.parse::<Token!(;)>()?;
+        let item: ForwardItem = input.parse()?;
+
+        Ok(ForwardBuiltinInput { trait_path, item })
+    }
+}
+
+/// Implement a standard library trait named by its bare name, when no forwardable trait of the
+/// same name is in scope to take precedence
+pub fn forward_builtin(input: TokenStream) -> Result<TokenStream> {
+    let ForwardBuiltinInput { trait_path, item } = parse2(input)?;
+    let forwarding = Forwarding::reparse(&item);
+    let mut errors = Errors::default();
+    let output = impl_builtin(&forwarding, &trait_path, &mut errors).expect("unknown builtin trait");
+    Ok(errors.finish(forwarding.hints.apply(output)?))
+}
+
+pub fn forward_trait(input: TokenStream) -> Result<TokenStream> {
+    let ForwardTraitInput { def, trait_path, item } = parse2(input)?;
+    let forwarding = Forwarding::reparse(&item);
+    forwarding.hints.apply(impl_forward_trait(&forwarding, &trait_path, &def)?)
+}
+
+fn impl_forward_trait(forwarding: &Forwarding, trait_path: &Path, def: &ItemTrait) -> Result<TokenStream> {
+    let Forwarding { item, arms, fallback, .. } = forwarding;
+    let item_ident = item.ident();
+    let trait_ident = &def.ident;
+
+    if let Some(segment) = trait_path.segments.last().filter(|s| !s.arguments.is_none()) {
+        return Err(Error::Other(segment.arguments.span(),
+                                "Forwarded traits are implemented for all of their generic parameters. \
+                                Remove the generic arguments".into()));
+    }
+
+    let mut taken = token_idents(item.to_token_stream());
+    taken.extend(token_idents(def.to_token_stream()));
+    let def = &rename_trait_generics(def, item, &mut taken)?;
+
+    // impl generics are the item's generics followed by the trait's
+    let mut generics = merge_generics(item.generics(), &def.generics);
+    let (_, ty_generics, _) = item.generics().split_for_impl();
+    let (_, trait_generics, _) = def.generics.split_for_impl();
+    let trait_ty = quote!(#trait_path #trait_generics);
+
+    let mut errors = Errors::default();
+    let mut assoc_types = vec![];
+    let mut methods = vec![];
+
+    for trait_item in &def.items {
+        match trait_item {
+            TraitItem::Type(t) => {
+                if !t.generics.params.is_empty() {
+                    errors.push(Error::Other(t.span(), "Generic associated types cannot be forwarded".into()));
+                }
+                assoc_types.push(t.ident.clone());
+            }
+            TraitItem::Fn(f) => {
+                methods.push(f);
+            }
+            other => {
+                errors.push(Error::Other(other.span(), "Only methods and associated types can be forwarded".into()));
+            }
+        }
+    }
+
+    // every variant type must implement the trait, with the same associated types. Like the
+    // `Output` of `Forward`, these are bound to new generic parameters on the impl
+    let assoc_params = assoc_types.iter().map(|a| fresh_ident(&a.to_string(), &mut taken)).collect_vec();
+    generics.params.extend(assoc_params.iter().map(|p| GenericParam::Type(TypeParam::from(p.clone()))));
+    let trait_bound = if assoc_types.is_empty() {
+        trait_ty.clone()
+    } else {
+        let params = def.generics.params.iter().map(generic_arg);
+        let bindings = assoc_types.iter().zip(&assoc_params).map(|(a, p)| quote!(#a = #p));
+        let args = params.chain(bindings);
+        quote!(#trait_path<#(#args),*>)
+    };
+
+    // bounds on concrete types are left out, as they stop rustc from normalizing the associated
+    // types of supertraits. Those impls are still checked, since the arms call them directly
+    let params = generics.params.iter().filter(|p| !matches!(p, GenericParam::Lifetime(_))).map(generic_arg).collect_vec();
+    let where_clause = generics.make_where_clause();
+    for ty in forwarding.inner_tys() {
+        let predicate = quote!(#ty : #trait_bound);
+        if mentions_any(predicate.clone(), &params) {
+            where_clause.predicates.push(parse2(predicate)?);
+        }
+    }
+    let (impl_generics, _, where_clause) = generics.split_for_impl();
+
+    let assoc_types = assoc_types.iter().zip(&assoc_params).map(|(a, p)| {
+        quote!(type #a = #p;)
+    }).collect_vec();
+
+    let overrides = arms.iter().map(|a| {
+        a.overrides.iter().filter(|o| o.trait_ident() == trait_ident).collect_vec()
+    }).collect_vec();
+
+    for o in overrides.iter().flatten() {
+        if !methods.iter().any(|m| &m.sig.ident == o.method_ident()) {
+            errors.push(Error::Other(o.method.span(),
+                                     format!("`{}` has no method `{}` to override", trait_ident, o.method_ident())));
+        }
+    }
+
+    let methods = methods.into_iter().map(|f| -> Result<TokenStream> {
+        let mut sig = f.sig.clone();
+        let method = &sig.ident;
+
+        let receiver = sig.receiver().ok_or_else(|| Error::Other(
+            sig.span(), format!("`{}::{}` has no `self` receiver and cannot be forwarded", trait_ident, method)))?;
+        if receiver.colon_token.is_some() {
+            return Err(Error::Other(receiver.span(), "Only `self`, `&self` and `&mut self` receivers can be forwarded".into()));
+        }
+
+        // rename arguments, since trait methods may use `_` or other patterns
+        let mut args = vec![];
+        for (i, input) in sig.inputs.iter_mut().enumerate() {
+            if let FnArg::Typed(typed) = input {
+                let ident = format_ident!("arg{}", i);
+                typed.attrs.clear();
+                *typed.pat = Pat::Ident(PatIdent {
+                    attrs: vec![],
+                    by_ref: None,
+                    mutability: None,
+                    ident: ident.clone(),
+                    subpat: None,
+                });
+                args.push(ident);
+            }
+        }
+
+        // pass generic arguments explicitly where possible, in case they can't be inferred
+        let uses_impl_trait = sig.inputs.iter().any(|i| contains_impl(i.to_token_stream()));
+        let method_generics = sig.generics.params.iter().filter(|p| !matches!(p, GenericParam::Lifetime(_))).map(generic_arg).collect_vec();
+        let turbofish = if method_generics.is_empty() || uses_impl_trait {
+            quote!()
+        } else {
+            quote!(::<#(#method_generics),*>)
+        };
+
+        let arms = arms.iter().zip(&overrides).map(|(VariantArm { arm, .. }, overrides)| {
+            let (inner_ty, pattern) = (arm.inner_ty, &arm.pattern);
+            match overrides.iter().find(|o| o.method_ident() == method) {
+                Some(ForwardOverride { func, .. }) => quote!(#pattern => #func(value, #(#args),*)),
+                None => quote!(#pattern => <#inner_ty as #trait_ty>::#method #turbofish(value, #(#args),*)),
+            }
+        });
+
+        Ok(quote! {
+            #[inline]
+            #sig {
+                match self {
+                    #(#arms,)*
+                    #fallback
+                }
+            }
+        })
+    }).filter_map(|m| errors.check(m)).collect_vec();
+
+    // an impl missing some of its items would only add more errors
+    errors.into_result()?;
+
+    let unsafety = &def.unsafety;
+
+    Ok(quote! {
+        #unsafety impl #impl_generics #trait_ty for #item_ident #ty_generics #where_clause {
+            #(#assoc_types)*
+            #(#methods)*
+        }
+    })
+}
+
+/// Rename any of the trait's generic parameters that clash with names used in the item
+fn rename_trait_generics(def: &ItemTrait, item: &ForwardItem, taken: &mut HashSet<String>) -> Result<ItemTrait> {
+    let item_idents = token_idents(item.to_token_stream());
+    let item_lifetimes = item.generics().lifetimes().map(|l| &l.lifetime).collect_vec();
+
+    let mut tokens = def.to_token_stream();
+    for param in &def.generics.params {
+        match param {
+            GenericParam::Type(TypeParam { ident, .. }) | GenericParam::Const(ConstParam { ident, .. })
+            if item_idents.contains(&ident.to_string()) => {
+                let renamed = fresh_ident(&ident.to_string(), taken);
+                tokens = replace_ident(tokens, ident, &renamed);
+            }
+            GenericParam::Lifetime(LifetimeParam { lifetime, .. }) if item_lifetimes.contains(&lifetime) => {
+                let renamed = Lifetime {
+                    apostrophe: lifetime.apostrophe,
+                    ident: fresh_ident(&lifetime.ident.to_string(), taken),
+                };
+                tokens = replace_lifetime(tokens, lifetime, &renamed);
+            }
+            _ => {}
+        }
+    }
+
+    Ok(parse2(tokens)?)
+}
+
+/// Combine two sets of generics, keeping lifetimes ahead of type and const parameters
+/// and dropping any defaults, which aren't allowed on impls
+fn merge_generics(a: &Generics, b: &Generics) -> Generics {
+    let mut params = a.params.iter().chain(b.params.iter()).cloned().map(|mut p| {
+        match &mut p {
+            GenericParam::Type(t) => {
+                t.eq_token = None;
+                t.default = None;
+            }
+            GenericParam::Const(c) => {
+                c.eq_token = None;
+                c.default = None;
+            }
+            GenericParam::Lifetime(_) => {}
+        }
+        p
+    }).collect_vec();
+    params.sort_by_key(|p| !matches!(p, GenericParam::Lifetime(_)));
+
+    let mut generics = Generics {
+        lt_token: Some(Default::default()),
+        params: params.into_iter().collect(),
+        gt_token: Some(Default::default()),
+        where_clause: None,
+    };
+
+    for where_clause in [&a.where_clause, &b.where_clause].into_iter().flatten() {
+        generics.make_where_clause().predicates.extend(where_clause.predicates.iter().cloned());
+    }
+
+    generics
+}
+
+/// Get a generic parameter as it would be written in a list of arguments
+fn generic_arg(param: &GenericParam) -> TokenStream {
+    match param {
+        GenericParam::Lifetime(l) => l.lifetime.to_token_stream(),
+        GenericParam::Type(t) => t.ident.to_token_stream(),
+        GenericParam::Const(c) => c.ident.to_token_stream(),
+    }
+}
+
+fn mentions_any(ts: TokenStream, idents: &[TokenStream]) -> bool {
+    ts.into_iter().any(|tt| match tt {
+        TokenTree::Ident(i) => idents.iter().any(|p| i == p.to_string()),
+        TokenTree::Group(g) => mentions_any(g.stream(), idents),
+        _ => false,
+    })
+}
+
+pub(crate) fn contains_impl(ts: TokenStream) -> bool {
+    ts.into_iter().any(|tt| match tt {
+        TokenTree::Ident(i) => i == "impl",
+        TokenTree::Group(g) => contains_impl(g.stream()),
+        _ => false,
+    })
+}
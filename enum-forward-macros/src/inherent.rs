@@ -0,0 +1,321 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use itertools::Itertools;
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote, ToTokens};
+use syn::{Attribute, Ident, ItemEnum, parse2, Path, Variant};
+
+use crate::common::{ForwardArm, ForwardItem, fresh_ident, normalize_type, snake_case, token_idents};
+use crate::error::{Error, Result};
+use crate::forward::{Forwarding, VariantArm};
+
+/// Check if a path in `#[forward(...)]` names `core::any::Any`
+pub(crate) fn is_any(path: &Path) -> bool {
+    let segments = path.segments.iter().map(|s| s.ident.to_string()).collect_vec();
+    let segments = match segments.as_slice() {
+        [krate, rest @ ..] if ["std", "core", "alloc"].contains(&krate.as_str()) => rest,
+        segments => segments,
+    };
+    matches!(segments, [any] if any == "Any") || matches!(segments, [module, any] if module == "any" && any == "Any")
+}
+
+/// The inherent methods that are generated when their name is listed in `#[forward(...)]`
+const METHODS: &[&str] = &["kind", "replace_inner"];
+
+/// Check if a path in `#[forward(...)]` names an inherent method to generate
+pub(crate) fn is_method(path: &Path) -> bool {
+    path.get_ident().is_some_and(|ident| METHODS.contains(&ident.to_string().as_str()))
+}
+
+/// Implement an inherent method listed in `#[forward(...)]`
+pub(crate) fn impl_method(forwarding: &Forwarding, method: &Ident) -> Result<TokenStream> {
+    let ForwardItem::Enum(item) = forwarding.item else {
+        return Err(Error::Other(method.span(), format!("`{}` can only be generated for enums", method)));
+    };
+    match method.to_string().as_str() {
+        "kind" => Ok(impl_kind(item, forwarding)),
+        "replace_inner" => impl_replace_inner(item, forwarding, method),
+        _ => unreachable!("not a generated method"),
+    }
+}
+
+/// Check whether a variant is marked with `#[forward(flatten)]`, so its generated accessors
+/// descend into the forwarding enum it contains instead of stopping at it
+pub(crate) fn forward_flattened(attrs: &[Attribute]) -> Result<bool> {
+    let mut flatten = false;
+    for attr in attrs.iter().filter(|a| a.path().is_ident("forward")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("flatten") {
+                flatten = true;
+                Ok(())
+            } else {
+                Err(meta.error("Unknown forward variant option"))
+            }
+        })?;
+    }
+    Ok(flatten)
+}
+
+/// Match arms for an accessor returning `value`, or the result of calling the same accessor on
+/// the inner value for flattened variants
+fn accessor_arms(arms: &[VariantArm], method: &Ident, value: TokenStream) -> Vec<TokenStream> {
+    arms.iter().map(|VariantArm { arm, flatten, .. }| {
+        let pattern = &arm.pattern;
+        if *flatten {
+            quote!(#pattern => value.#method())
+        } else {
+            quote!(#pattern => #value)
+        }
+    }).collect()
+}
+
+/// Inherent methods exposing the inner value as `&dyn Any`, so it can be downcast without
+/// knowing the variant types
+pub(crate) fn impl_any(forwarding: &Forwarding) -> Result<TokenStream> {
+    let Forwarding { item, arms, fallback, .. } = forwarding;
+    let item_ident = item.ident();
+    let vis = item.vis();
+    let as_any_arms = accessor_arms(arms, &format_ident!("as_any"), quote!(value));
+    let as_any_mut_arms = accessor_arms(arms, &format_ident!("as_any_mut"), quote!(value));
+
+    let downcast = fresh_ident("T", &mut token_idents(item.to_token_stream()));
+
+    let (_, ty_generics, _) = item.generics().split_for_impl();
+    let mut generics = item.generics().clone();
+    let where_clause = generics.make_where_clause();
+    for ty in forwarding.inner_tys() {
+        where_clause.predicates.push(parse2(quote!(#ty : ::core::any::Any))?);
+    }
+    let (impl_generics, _, where_clause) = generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics #item_ident #ty_generics #where_clause {
+            /// Get the inner value as `&dyn Any`
+            #[inline]
+            #vis fn as_any(&self) -> &dyn ::core::any::Any {
+                match self {
+                    #(#as_any_arms,)*
+                    #fallback
+                }
+            }
+
+            /// Get the inner value as `&mut dyn Any`
+            #[inline]
+            #vis fn as_any_mut(&mut self) -> &mut dyn ::core::any::Any {
+                match self {
+                    #(#as_any_mut_arms,)*
+                    #fallback
+                }
+            }
+
+            /// Get the `TypeId` of the inner value
+            #[inline]
+            #vis fn inner_type_id(&self) -> ::core::any::TypeId {
+                <dyn ::core::any::Any>::type_id(self.as_any())
+            }
+
+            /// Check if the inner value has a given type
+            #[inline]
+            #vis fn is<#downcast: ::core::any::Any>(&self) -> bool {
+                self.as_any().is::<#downcast>()
+            }
+
+            /// Get a reference to the inner value if it has a given type
+            #[inline]
+            #vis fn downcast_ref<#downcast: ::core::any::Any>(&self) -> ::core::option::Option<&#downcast> {
+                self.as_any().downcast_ref::<#downcast>()
+            }
+
+            /// Get a mutable reference to the inner value if it has a given type
+            #[inline]
+            #vis fn downcast_mut<#downcast: ::core::any::Any>(&mut self) -> ::core::option::Option<&mut #downcast> {
+                self.as_any_mut().downcast_mut::<#downcast>()
+            }
+        }
+    })
+}
+
+/// Inherent methods converting the inner value to a `dyn Trait` object
+pub(crate) fn impl_dyn(forwarding: &Forwarding, trait_path: &Path) -> Result<TokenStream> {
+    let Forwarding { item, krate, arms, fallback, .. } = forwarding;
+    let trait_ident = &trait_path.segments.last().unwrap().ident;
+    let item_ident = item.ident();
+    let vis = item.vis();
+    let inner_tys = forwarding.inner_tys();
+
+    let snake = snake_case(&trait_ident.to_string());
+    let as_dyn = format_ident!("as_dyn_{}", snake);
+    let as_dyn_mut = format_ident!("as_dyn_{}_mut", snake);
+    let into_box_dyn = format_ident!("into_box_dyn_{}", snake);
+
+    let as_dyn_arms = accessor_arms(arms, &as_dyn, quote!(value));
+    let as_dyn_mut_arms = accessor_arms(arms, &as_dyn_mut, quote!(value));
+    let into_box_arms = accessor_arms(arms, &into_box_dyn, quote!(#krate::__private::Box::new(value)));
+
+    let (_, ty_generics, _) = item.generics().split_for_impl();
+    let mut generics = item.generics().clone();
+    let where_clause = generics.make_where_clause();
+    // flattened variants are converted by the nested enum, which doesn't need to implement the trait
+    for ty in arms.iter().filter(|a| !a.flatten).map(|a| a.arm.inner_ty).unique() {
+        where_clause.predicates.push(parse2(quote!(#ty : #trait_path))?);
+    }
+    let (impl_generics, _, where_clause) = generics.split_for_impl();
+
+    // the boxed trait object is `'static`, so only that method needs the inner types to be
+    let into_box = cfg!(feature = "alloc").then(|| quote! {
+        /// Convert the item into a boxed trait object of its inner value
+        #[inline]
+        #vis fn #into_box_dyn(self) -> #krate::__private::Box<dyn #trait_path>
+            where #(#inner_tys : 'static),*
+        {
+            match self {
+                #(#into_box_arms,)*
+                #fallback
+            }
+        }
+    });
+
+    Ok(quote! {
+        impl #impl_generics #item_ident #ty_generics #where_clause {
+            /// Get the inner value as a trait object
+            #[inline]
+            #vis fn #as_dyn(&self) -> &dyn #trait_path {
+                match self {
+                    #(#as_dyn_arms,)*
+                    #fallback
+                }
+            }
+
+            /// Get the inner value as a mutable trait object
+            #[inline]
+            #vis fn #as_dyn_mut(&mut self) -> &mut dyn #trait_path {
+                match self {
+                    #(#as_dyn_mut_arms,)*
+                    #fallback
+                }
+            }
+
+            #into_box
+        }
+    })
+}
+
+/// Find the variant marked with `#[default_variant]`, which `take` leaves in place of the value
+fn default_variant(item: &ItemEnum) -> Result<Option<&Variant>> {
+    let mut default: Option<&Variant> = None;
+    for variant in &item.variants {
+        for attr in variant.attrs.iter().filter(|a| a.path().is_ident("default_variant")) {
+            attr.meta.require_path_only()?;
+            if let Some(first) = default {
+                return Err(Error::Syn(syn::Error::new_spanned(attr, "Only one variant can be the `#[default_variant]`"))
+                    .note(&first.ident, "the first is marked here"));
+            }
+            default = Some(variant);
+        }
+    }
+    Ok(default)
+}
+
+/// Inherent methods taking the value out of an enum with a `#[default_variant]`, and replacing
+/// it with a transformed one
+pub(crate) fn impl_take(item: &ItemEnum, forwarding: &Forwarding) -> Result<TokenStream> {
+    let Some(default) = default_variant(item)? else {
+        return Ok(TokenStream::new());
+    };
+    let Some(ForwardArm { inner_ty, pattern, .. }) = forwarding.arms.iter()
+        .map(|a| &a.arm)
+        .find(|a| a.variant.is_some_and(|v| v.ident == default.ident)) else {
+        return Ok(TokenStream::new());
+    };
+    let krate = &forwarding.krate;
+    let item_ident = &item.ident;
+    let vis = &item.vis;
+    let mapper = fresh_ident("M", &mut token_idents(item.to_token_stream()));
+    let (impl_generics, ty_generics, where_clause) = item.generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics #item_ident #ty_generics #where_clause {
+            /// Take the value out, leaving the default variant in its place
+            #[inline]
+            #vis fn take(&mut self) -> Self where #inner_ty : ::core::default::Default {
+                let value = ::core::default::Default::default();
+                ::core::mem::replace(self, #pattern)
+            }
+
+            /// Replace the inner value with the result of passing it to `mapper`, keeping its
+            /// variant. If `mapper` panics, the default variant is left in its place
+            #[inline]
+            #vis fn replace_with<#mapper>(&mut self, mapper : #mapper)
+                where #inner_ty : ::core::default::Default, Self : #krate::MapInner<#mapper>
+            {
+                *self = #krate::MapInner::map_inner(self.take(), mapper);
+            }
+        }
+    })
+}
+
+/// Implement `replace_inner` for enums whose variants all hold the same type, swapping the inner
+/// value without changing the variant
+fn impl_replace_inner(item: &ItemEnum, forwarding: &Forwarding, method: &Ident) -> Result<TokenStream> {
+    let Forwarding { arms, fallback, .. } = forwarding;
+    if arms.is_empty() {
+        return Ok(TokenStream::new());
+    }
+    let Ok(inner_ty) = arms.iter().map(|a| a.arm.inner_ty).unique_by(|ty| normalize_type(ty)).exactly_one() else {
+        return Err(Error::Other(method.span(), "`replace_inner` needs every variant to hold the same type".into()));
+    };
+    let item_ident = &item.ident;
+    let vis = &item.vis;
+    let replacement = fresh_ident("inner", &mut token_idents(item.to_token_stream()));
+    let arms = arms.iter().map(|VariantArm { arm, .. }| {
+        let pattern = &arm.pattern;
+        quote!(#pattern => ::core::mem::replace(value, #replacement))
+    });
+    let (impl_generics, ty_generics, where_clause) = item.generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics #item_ident #ty_generics #where_clause {
+            /// Replace the inner value, keeping its variant, and return the old value
+            #[inline]
+            #vis fn replace_inner(&mut self, #replacement : #inner_ty) -> #inner_ty {
+                match self {
+                    #(#arms,)*
+                    #fallback
+                }
+            }
+        }
+    })
+}
+
+/// Implement `kind`, naming the variant a value is, or the variant of the nested enum for
+/// flattened variants, which needs to generate `kind` as well
+fn impl_kind(item: &ItemEnum, forwarding: &Forwarding) -> TokenStream {
+    let item_ident = &item.ident;
+    let vis = &item.vis;
+    let flattened = forwarding.arms.iter().filter(|a| a.flatten).map(|a| &a.arm).collect_vec();
+    let arms = item.variants.iter().map(|variant| {
+        let var_ident = &variant.ident;
+        match flattened.iter().find(|a| a.variant.is_some_and(|v| v.ident == *var_ident)) {
+            Some(ForwardArm { pattern, .. }) => quote!(#pattern => value.kind()),
+            None => {
+                let name = var_ident.to_string();
+                quote!(#item_ident::#var_ident { .. } => #name)
+            }
+        }
+    });
+    let (impl_generics, ty_generics, where_clause) = item.generics.split_for_impl();
+
+    quote! {
+        impl #impl_generics #item_ident #ty_generics #where_clause {
+            /// Get the name of the variant holding the value, descending into flattened variants
+            #[inline]
+            #vis fn kind(&self) -> &'static str {
+                match self {
+                    #(#arms,)*
+                }
+            }
+        }
+    }
+}
@@ -2,42 +2,211 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
+use crate::compare::{CmpTrait, derive_compare2};
 use crate::convert::{derive_enum_from2, derive_enum_tryinto2};
 
+mod builtin;
+mod compare;
 mod convert;
 mod common;
+mod each;
 mod error;
+mod flatten;
+#[cfg(test)]
+mod expand;
 mod forward;
+mod forward_trait;
+mod inherent;
+mod ops;
+#[cfg(feature = "serde")]
+mod serialize;
+mod state;
+mod visitor;
 
-#[proc_macro_derive(From)]
+/// Implement `From` for the type of each variant. Variants marked `#[from(flatten)]` also convert
+/// from the variant types of the enum they contain, which needs `#[from(register)]` to declare
+/// the hidden macro describing its variants. Their types are written as that enum declares them,
+/// so they have to be nameable where it's flattened.
+#[proc_macro_derive(From, attributes(enum_forward, from))]
 pub fn derive_enum_from(item: proc_macro::TokenStream) -> proc_macro::TokenStream {
     match derive_enum_from2(item.into()) {
         Ok(output) => output.into(),
-        Err(err) => err.to_compile_error(),
+        Err(err) => err.into_compile_error(),
     }
 }
 
-#[proc_macro_derive(TryInto)]
+#[proc_macro_derive(TryInto, attributes(enum_forward, from))]
 pub fn derive_enum_tryinto(item: proc_macro::TokenStream) -> proc_macro::TokenStream {
     match derive_enum_tryinto2(item.into()) {
         Ok(output) => output.into(),
-        Err(err) => err.to_compile_error(),
+        Err(err) => err.into_compile_error(),
     }
 }
 
 
-#[proc_macro_derive(Forward)]
+/// Forward to the inner value of each variant, or the marked field of a struct. Traits listed in
+/// `#[forward(...)]` are implemented through the macro `#[forwardable]` declares beside them, or
+/// directly for standard library traits. A bare name like `Add` implements a forwardable trait
+/// of that name imported or declared in the item's module, and the standard library trait
+/// otherwise. Derives of the same name, like `thiserror::Error`, don't interfere, but a forwardable
+/// trait brought in by a glob import is ambiguous with the standard library one, so import it by
+/// name or write its path. `Debug` always means the standard library trait.
+///
+/// `#[forward(kind)]` on an enum adds `kind(&self) -> &'static str`, naming the variant holding
+/// the value. It descends into `#[forward(flatten)]` variants, whose enums need `#[forward(kind)]`
+/// as well. `#[forward(replace_inner)]` on an enum whose variants all hold the same type adds
+/// `replace_inner(&mut self, inner)`, which swaps the inner value and returns the old one.
+///
+/// Marking a variant `#[default_variant]` adds `take(&mut self)`, leaving that variant in place of
+/// the value, and `replace_with(&mut self, mapper)`, which maps the inner value with a `Mapper` or
+/// closure and keeps its variant. `replace_with` takes the value out first, so it's only generated
+/// with a default variant, which is left in place if the mapper panics.
+#[proc_macro_derive(Forward, attributes(enum_forward, default_variant, forward, forward_ops, forward_override))]
 pub fn forwarding(item: proc_macro::TokenStream) -> proc_macro::TokenStream {
     match forward::forwarding2(item.into()) {
         Ok(output) => output.into(),
-        Err(err) => err.to_compile_error().into(),
+        Err(err) => err.into_compile_error(),
     }
 }
 
+/// Implement a function by forwarding to the inner value of an enum deriving `Forward`, written
+/// `#[forward_to([arg:] Type as Trait + ...)]`. The value forwarded is `self`, or the named
+/// argument for functions without a receiver. Without a body, the method of the same name is
+/// called on the inner value. With a body, it is run with `self` bound to the inner value.
+/// `&mut self` and `&mut` arguments give mutable access to the inner value, while `self` borrows
+/// it the same way as `&self`.
 #[proc_macro_attribute]
 pub fn forward_to(attr: proc_macro::TokenStream, item: proc_macro::TokenStream) -> proc_macro::TokenStream {
     match forward::forward_to(attr.into(), item.into()) {
         Ok(output) => output.into(),
-        Err(err) => err.to_compile_error().into(),
+        Err(err) => err.into_compile_error(),
     }
-}
\ No newline at end of file
+}
+
+#[proc_macro_derive(ForwardEach, attributes(enum_forward, forward_each))]
+pub fn derive_forward_each(item: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    match each::derive_forward_each2(item.into()) {
+        Ok(output) => output.into(),
+        Err(err) => err.into_compile_error(),
+    }
+}
+
+#[proc_macro_derive(ForwardPartialEq, attributes(enum_forward, forward_cmp))]
+pub fn derive_partial_eq(item: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    match derive_compare2(item.into(), CmpTrait::PartialEq) {
+        Ok(output) => output.into(),
+        Err(err) => err.into_compile_error(),
+    }
+}
+
+#[proc_macro_derive(ForwardEq, attributes(enum_forward, forward_cmp))]
+pub fn derive_eq(item: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    match derive_compare2(item.into(), CmpTrait::Eq) {
+        Ok(output) => output.into(),
+        Err(err) => err.into_compile_error(),
+    }
+}
+
+#[proc_macro_derive(ForwardPartialOrd, attributes(enum_forward, forward_cmp))]
+pub fn derive_partial_ord(item: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    match derive_compare2(item.into(), CmpTrait::PartialOrd) {
+        Ok(output) => output.into(),
+        Err(err) => err.into_compile_error(),
+    }
+}
+
+#[proc_macro_derive(ForwardOrd, attributes(enum_forward, forward_cmp))]
+pub fn derive_ord(item: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    match derive_compare2(item.into(), CmpTrait::Ord) {
+        Ok(output) => output.into(),
+        Err(err) => err.into_compile_error(),
+    }
+}
+
+#[proc_macro_derive(ForwardHash, attributes(enum_forward, forward_cmp))]
+pub fn derive_hash(item: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    match derive_compare2(item.into(), CmpTrait::Hash) {
+        Ok(output) => output.into(),
+        Err(err) => err.into_compile_error(),
+    }
+}
+
+/// Implement `Serialize` by serializing the inner value of each variant. Needs the `serde`
+/// feature, which depends on `std`.
+#[cfg(feature = "serde")]
+#[proc_macro_derive(ForwardSerialize, attributes(enum_forward, forward))]
+pub fn derive_serialize(item: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    match serialize::derive_serialize2(item.into()) {
+        Ok(output) => output.into(),
+        Err(err) => err.into_compile_error(),
+    }
+}
+
+/// Implement `Deserialize` by trying each variant's type in order on the buffered input. Needs
+/// the `serde` feature, which depends on `std`, as the input is buffered with `serde-value`.
+#[cfg(feature = "serde")]
+#[proc_macro_derive(ForwardDeserialize, attributes(enum_forward))]
+pub fn derive_deserialize(item: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    match serialize::derive_deserialize2(item.into()) {
+        Ok(output) => output.into(),
+        Err(err) => err.into_compile_error(),
+    }
+}
+
+/// Implement `Step` for an enum of states that each implement it. With
+/// `#[state_machine(transitions(A => B | C, ...))]`, states whose `Step::Next` is another state
+/// type must be allowed to move to it, or stepping doesn't compile. States stepping to the enum
+/// itself can return any state, so their transitions are only checked with debug assertions.
+#[proc_macro_derive(StateMachine, attributes(enum_forward, state_machine))]
+pub fn derive_state_machine(item: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    match state::derive_state_machine2(item.into()) {
+        Ok(output) => output.into(),
+        Err(err) => err.into_compile_error(),
+    }
+}
+
+#[proc_macro_derive(ForwardVisitor, attributes(enum_forward))]
+pub fn derive_visitor(item: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    match visitor::derive_visitor2(item.into()) {
+        Ok(output) => output.into(),
+        Err(err) => err.into_compile_error(),
+    }
+}
+
+/// Let a trait be implemented with `#[forward(Trait)]`. This declares a hidden macro sharing the
+/// trait's name, so it can be forwarded wherever the trait is imported, including from other
+/// crates for public traits.
+#[proc_macro_attribute]
+pub fn forwardable(attr: proc_macro::TokenStream, item: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    match forward_trait::forwardable(attr.into(), item.into()) {
+        Ok(output) => output.into(),
+        Err(err) => err.into_compile_error(),
+    }
+}
+
+#[doc(hidden)]
+#[proc_macro]
+pub fn __flatten_variant(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    match flatten::flatten_variant(input.into()) {
+        Ok(output) => output.into(),
+        Err(err) => err.into_compile_error(),
+    }
+}
+
+#[doc(hidden)]
+#[proc_macro]
+pub fn __forward_builtin(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    match forward_trait::forward_builtin(input.into()) {
+        Ok(output) => output.into(),
+        Err(err) => err.into_compile_error(),
+    }
+}
+
+#[doc(hidden)]
+#[proc_macro]
+pub fn __forward_trait(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    match forward_trait::forward_trait(input.into()) {
+        Ok(output) => output.into(),
+        Err(err) => err.into_compile_error(),
+    }
+}
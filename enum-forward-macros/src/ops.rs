@@ -0,0 +1,253 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use itertools::Itertools;
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{Attribute, GenericArgument, Ident, parse2, Path, PathArguments, Type};
+use syn::spanned::Spanned;
+
+use crate::common::{ForwardItem, generic_types, replace_ident};
+use crate::error::{Error, Result};
+use crate::forward::Forwarding;
+
+#[derive(Clone, Copy, PartialEq)]
+enum OpKind {
+    /// `-value`, rewrapped in its variant
+    Unary,
+    /// `value + rhs`, rewrapped in its variant
+    Binary,
+    /// `value += rhs`
+    Assign,
+}
+
+struct Operator {
+    name: &'static str,
+    method: &'static str,
+    kind: OpKind,
+}
+
+const OPERATORS: &[Operator] = &[
+    Operator { name: "Neg", method: "neg", kind: OpKind::Unary },
+    Operator { name: "Not", method: "not", kind: OpKind::Unary },
+    Operator { name: "Add", method: "add", kind: OpKind::Binary },
+    Operator { name: "Sub", method: "sub", kind: OpKind::Binary },
+    Operator { name: "Mul", method: "mul", kind: OpKind::Binary },
+    Operator { name: "Div", method: "div", kind: OpKind::Binary },
+    Operator { name: "Rem", method: "rem", kind: OpKind::Binary },
+    Operator { name: "BitAnd", method: "bitand", kind: OpKind::Binary },
+    Operator { name: "BitOr", method: "bitor", kind: OpKind::Binary },
+    Operator { name: "BitXor", method: "bitxor", kind: OpKind::Binary },
+    Operator { name: "Shl", method: "shl", kind: OpKind::Binary },
+    Operator { name: "Shr", method: "shr", kind: OpKind::Binary },
+    Operator { name: "AddAssign", method: "add_assign", kind: OpKind::Assign },
+    Operator { name: "SubAssign", method: "sub_assign", kind: OpKind::Assign },
+    Operator { name: "MulAssign", method: "mul_assign", kind: OpKind::Assign },
+    Operator { name: "DivAssign", method: "div_assign", kind: OpKind::Assign },
+    Operator { name: "RemAssign", method: "rem_assign", kind: OpKind::Assign },
+    Operator { name: "BitAndAssign", method: "bitand_assign", kind: OpKind::Assign },
+    Operator { name: "BitOrAssign", method: "bitor_assign", kind: OpKind::Assign },
+    Operator { name: "BitXorAssign", method: "bitxor_assign", kind: OpKind::Assign },
+    Operator { name: "ShlAssign", method: "shl_assign", kind: OpKind::Assign },
+    Operator { name: "ShrAssign", method: "shr_assign", kind: OpKind::Assign },
+];
+
+/// Look up an operator trait from `core::ops` by the path it was named with in `#[forward(...)]`
+fn operator(path: &Path) -> Option<&'static Operator> {
+    let segments = path.segments.iter().map(|s| s.ident.to_string()).collect_vec();
+    let name = match segments.iter().map(String::as_str).collect_vec().as_slice() {
+        [name] | ["ops", name] | ["std" | "core", "ops", name] => name.to_string(),
+        _ => return None,
+    };
+    OPERATORS.iter().find(|o| o.name == name)
+}
+
+/// Check if a path in `#[forward(...)]` names an operator trait
+pub(crate) fn is_operator(path: &Path) -> bool {
+    operator(path).is_some()
+}
+
+/// What a binary operator does when its operands are different variants, set with
+/// `#[forward_ops(mismatch = ...)]`
+#[derive(Clone, Copy, PartialEq)]
+pub(crate) enum Mismatch {
+    Panic,
+    Error,
+}
+
+pub(crate) fn mismatch(attrs: &[Attribute]) -> Result<Mismatch> {
+    let mut mismatch = Mismatch::Panic;
+    for attr in attrs.iter().filter(|a| a.path().is_ident("forward_ops")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("mismatch") {
+                let value: Ident = meta.value()?.parse()?;
+                mismatch = match value.to_string().as_str() {
+                    "panic" => Mismatch::Panic,
+                    "error" => Mismatch::Error,
+                    _ => return Err(syn::Error::new(value.span(), "Unknown mismatch, expected `panic` or `error`")),
+                };
+                Ok(())
+            } else {
+                Err(meta.error("Unknown forward_ops option"))
+            }
+        })?;
+    }
+    Ok(mismatch)
+}
+
+/// Implement an operator trait from `core::ops`. Unary operators and binary operators with a
+/// right-hand side type given, like `Mul<f32>`, apply to each variant's value. Binary operators
+/// without one apply to two values of the same variant.
+pub(crate) fn impl_operator(forwarding: &Forwarding, trait_path: &Path) -> Result<TokenStream> {
+    let Forwarding { item, krate, mismatch, arms, fallback, .. } = forwarding;
+    let op = operator(trait_path).expect("not an operator");
+    let ForwardItem::Enum(item_enum) = item else {
+        return Err(Error::Other(trait_path.span(), "Operators can only be forwarded by enums".into()));
+    };
+    let item_ident = item.ident();
+    let op_trait = format_ident!("{}", op.name);
+    let op_trait = quote!(::core::ops::#op_trait);
+    let method = format_ident!("{}", op.method);
+
+    let rhs = match &trait_path.segments.last().unwrap().arguments {
+        PathArguments::None => None,
+        PathArguments::AngleBracketed(args) if op.kind != OpKind::Unary && args.args.len() == 1 => {
+            match &args.args[0] {
+                GenericArgument::Type(ty) => Some(ty.clone()),
+                other => return Err(Error::Other(other.span(), "Expected the type of the right-hand side".into())),
+            }
+        }
+        other => return Err(Error::Other(other.span(), format!("Unexpected arguments to `{}`", op.name))),
+    };
+
+    let bound = |ty: &Type| match (op.kind, &rhs) {
+        (OpKind::Unary, _) => quote!(#ty : #op_trait<Output = #ty>),
+        (OpKind::Binary, Some(rhs)) => quote!(#ty : #op_trait<#rhs, Output = #ty>),
+        (OpKind::Binary, None) => quote!(#ty : #op_trait<Output = #ty>),
+        (OpKind::Assign, Some(rhs)) => quote!(#ty : #op_trait<#rhs>),
+        (OpKind::Assign, None) => quote!(#ty : #op_trait),
+    };
+    let (_, ty_generics, _) = item.generics().split_for_impl();
+    let mut generics = item.generics().clone();
+    let where_clause = generics.make_where_clause();
+    for ty in generic_types(item.generics(), arms.iter().map(|a| a.arm.inner_ty)) {
+        where_clause.predicates.push(parse2(bound(ty))?);
+    }
+    let (impl_generics, _, where_clause) = generics.split_for_impl();
+
+    let patterns = arms.iter().map(|a| &a.arm.pattern).collect_vec();
+
+    // operands of different variants
+    let variant_name = |of: TokenStream| {
+        let name_arms = item_enum.variants.iter().map(|v| {
+            let var_ident = &v.ident;
+            let name = format!("{}::{}", item_ident, var_ident);
+            quote!(#item_ident::#var_ident { .. } => #name)
+        });
+        quote!(match #of { #(#name_arms,)* })
+    };
+    let (left_name, right_name) = (variant_name(quote!(&left)), variant_name(quote!(&right)));
+    let mismatched = quote!(#krate::VariantMismatch { left: #left_name, right: #right_name });
+    let pair_fallback = if item_enum.variants.len() > 1 {
+        Some(match (op.kind, mismatch) {
+            (OpKind::Binary, Mismatch::Error) => quote!((left, right) => ::core::result::Result::Err(#mismatched)),
+            _ => quote!((left, right) => ::core::panic!("{}", #mismatched)),
+        })
+    } else {
+        fallback.clone()
+    };
+    // arms for operands of the same variant, binding the right-hand side's value to `rhs`
+    let pair_arms = |body: &dyn Fn(&TokenStream) -> TokenStream| patterns.iter().map(|pattern| {
+        let rhs_pattern = replace_ident((*pattern).clone(), &format_ident!("value"), &format_ident!("rhs"));
+        let body = body(pattern);
+        quote!((#pattern, #rhs_pattern) => #body)
+    }).collect_vec();
+
+    let output = match (op.kind, &rhs) {
+        (OpKind::Unary, _) => {
+            quote! {
+                impl #impl_generics #op_trait for #item_ident #ty_generics #where_clause {
+                    type Output = Self;
+
+                    #[inline]
+                    fn #method(self) -> Self {
+                        match self {
+                            #(#patterns => { let value = #op_trait::#method(value); #patterns },)*
+                            #fallback
+                        }
+                    }
+                }
+            }
+        }
+        (OpKind::Binary, Some(rhs)) => {
+            quote! {
+                impl #impl_generics #op_trait<#rhs> for #item_ident #ty_generics #where_clause {
+                    type Output = Self;
+
+                    #[inline]
+                    fn #method(self, rhs : #rhs) -> Self {
+                        match self {
+                            #(#patterns => { let value = #op_trait::#method(value, rhs); #patterns },)*
+                            #fallback
+                        }
+                    }
+                }
+            }
+        }
+        (OpKind::Binary, None) => {
+            let (output_ty, wrap) = match mismatch {
+                Mismatch::Panic => (quote!(Self), quote!()),
+                Mismatch::Error => (
+                    quote!(::core::result::Result<Self, #krate::VariantMismatch>),
+                    quote!(::core::result::Result::Ok),
+                ),
+            };
+            let same = pair_arms(&|pattern| quote!(#wrap({ let value = #op_trait::#method(value, rhs); #pattern })));
+            quote! {
+                impl #impl_generics #op_trait for #item_ident #ty_generics #where_clause {
+                    type Output = #output_ty;
+
+                    #[inline]
+                    #[track_caller]
+                    fn #method(self, rhs : Self) -> #output_ty {
+                        match (self, rhs) {
+                            #(#same,)*
+                            #pair_fallback
+                        }
+                    }
+                }
+            }
+        }
+        (OpKind::Assign, Some(rhs)) => {
+            quote! {
+                impl #impl_generics #op_trait<#rhs> for #item_ident #ty_generics #where_clause {
+                    #[inline]
+                    fn #method(&mut self, rhs : #rhs) {
+                        match self {
+                            #(#patterns => #op_trait::#method(value, rhs),)*
+                            #fallback
+                        }
+                    }
+                }
+            }
+        }
+        (OpKind::Assign, None) => {
+            let same = pair_arms(&|_| quote!(#op_trait::#method(value, rhs)));
+            quote! {
+                impl #impl_generics #op_trait for #item_ident #ty_generics #where_clause {
+                    #[inline]
+                    #[track_caller]
+                    fn #method(&mut self, rhs : Self) {
+                        match (self, rhs) {
+                            #(#same,)*
+                            #pair_fallback
+                        }
+                    }
+                }
+            }
+        }
+    };
+
+    Ok(output)
+}
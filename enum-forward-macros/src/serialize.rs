@@ -0,0 +1,101 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use itertools::Itertools;
+use proc_macro2::{Span, TokenStream};
+use quote::{quote, ToTokens};
+use syn::{GenericParam, ItemEnum, Lifetime, LifetimeParam, parse2};
+
+use crate::common::{display_tokens, ForwardItem, fresh_ident, generic_types, item_options, ItemOptions, token_idents, variant_patterns, VariantInfo};
+use crate::error::{Errors, Result};
+
+/// Serialize an item as its inner value, like `#[serde(untagged)]`
+pub(crate) fn derive_serialize2(item: TokenStream) -> Result<TokenStream> {
+    let item = parse2::<ForwardItem>(item)?;
+    let item_ident = item.ident();
+    let mut errors = Errors::default();
+    let ItemOptions { krate, hints } = errors.check(item_options(item.attrs())).unwrap_or_default();
+    let serde = quote!(#krate::serde_impl::serde);
+
+    let mut taken = token_idents(item.to_token_stream());
+    let serializer = fresh_ident("S", &mut taken);
+
+    let arms = item.arms(&mut errors);
+    if arms.is_empty() {
+        return Ok(errors.finish(TokenStream::new()));
+    }
+    let fallback = item.fallback_arm(&arms);
+
+    let mut generics = item.generics().clone();
+    let bounded = generic_types(item.generics(), arms.iter().map(|a| a.inner_ty));
+    let where_clause = generics.make_where_clause();
+    for ty in bounded {
+        where_clause.predicates.push(parse2(quote!(#ty : #serde::Serialize))?);
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let patterns = arms.iter().map(|a| &a.pattern);
+
+    let output = quote! {
+        impl #impl_generics #serde::Serialize for #item_ident #ty_generics #where_clause {
+            fn serialize<#serializer : #serde::Serializer>(&self, serializer : #serializer)
+                -> ::core::result::Result<#serializer::Ok, #serializer::Error> {
+                match self {
+                    #(#patterns => #serde::Serialize::serialize(value, serializer),)*
+                    #fallback
+                }
+            }
+        }
+    };
+
+    Ok(errors.finish(hints.apply(output)?))
+}
+
+/// Deserialize an enum by trying each variant's type in order, keeping every error in case none
+/// of them match
+pub(crate) fn derive_deserialize2(item: TokenStream) -> Result<TokenStream> {
+    let item = parse2::<ItemEnum>(item)?;
+    let item_ident = &item.ident;
+    let mut errors = Errors::default();
+    let ItemOptions { krate, hints } = errors.check(item_options(&item.attrs)).unwrap_or_default();
+    let serde = quote!(#krate::serde_impl::serde);
+
+    let mut taken = token_idents(item.to_token_stream());
+    let deserializer = fresh_ident("D", &mut taken);
+    let lifetime = Lifetime::new(&format!("'{}", fresh_ident("de", &mut taken)), Span::call_site());
+
+    let variants = variant_patterns(&item).filter_map(|v| errors.check(v)).collect_vec();
+
+    let (_, ty_generics, _) = item.generics.split_for_impl();
+    let mut generics = item.generics.clone();
+    generics.params.insert(0, GenericParam::Lifetime(LifetimeParam::new(lifetime.clone())));
+    let bounded = generic_types(&item.generics, variants.iter().map(|v| v.inner_ty));
+    let where_clause = generics.make_where_clause();
+    for ty in bounded {
+        where_clause.predicates.push(parse2(quote!(#ty : #serde::Deserialize<#lifetime>))?);
+    }
+    let (impl_generics, _, where_clause) = generics.split_for_impl();
+
+    let attempts = variants.iter().map(|VariantInfo { variant, inner_ty, pattern }| {
+        let name = format!("{} ({})", variant.ident, display_tokens(inner_ty));
+        quote! {
+            if let ::core::option::Option::Some(value) = attempts.attempt::<#inner_ty>(#name) {
+                return ::core::result::Result::Ok(#pattern);
+            }
+        }
+    });
+    let item_name = item_ident.to_string();
+
+    let output = quote! {
+        impl #impl_generics #serde::Deserialize<#lifetime> for #item_ident #ty_generics #where_clause {
+            fn deserialize<#deserializer : #serde::Deserializer<#lifetime>>(deserializer : #deserializer)
+                -> ::core::result::Result<Self, #deserializer::Error> {
+                let mut attempts = #krate::serde_impl::Attempts::new(deserializer)?;
+                #(#attempts)*
+                ::core::result::Result::Err(attempts.fail(#item_name))
+            }
+        }
+    };
+
+    Ok(errors.finish(hints.apply(output)?))
+}
@@ -0,0 +1,167 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use itertools::Itertools;
+use proc_macro2::TokenStream;
+use quote::{quote, ToTokens};
+use syn::{Attribute, GenericParam, Ident, ItemEnum, parse2, parse_quote, Token, Type, TypeParam};
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::spanned::Spanned;
+
+use crate::common::{fallback_arm, fresh_ident, item_options, ItemOptions, normalize_type, token_idents, variant_patterns, VariantInfo};
+use crate::error::{Error, Errors, Result};
+
+/// The states a state can move to, written `Idle => Handshake | Closed`
+struct Transitions {
+    from: Ident,
+    to: Vec<Ident>,
+}
+
+impl Parse for Transitions {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let from = input.parse()?;
+        input.parse::<Token!(=>)>()?;
+        let to = Punctuated::<Ident, Token!(|)>::parse_separated_nonempty(input)?;
+        Ok(Transitions { from, to: to.into_iter().collect() })
+    }
+}
+
+/// Parse the table of allowed transitions from `#[state_machine(transitions(...))]`, if given
+fn transition_table(attrs: &[Attribute]) -> Result<Option<Vec<Transitions>>> {
+    let mut table = None;
+    for attr in attrs.iter().filter(|a| a.path().is_ident("state_machine")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("transitions") {
+                let content;
+                syn::parenthesized!(content in meta.input);
+                let transitions = content.parse_terminated(Transitions::parse, Token!(,))?;
+                table.get_or_insert_with(Vec::new).extend(transitions);
+                Ok(())
+            } else {
+                Err(meta.error("Unknown state_machine option"))
+            }
+        })?;
+    }
+    Ok(table)
+}
+
+/// Implement `Step` for an enum of states by stepping the inner state and converting the state it
+/// moves to back into the enum. With a table of transitions, each state's `Step::Next` type must
+/// be one it's allowed to move to, and states returned as the enum are checked in debug builds.
+/// The transitions are scoped to the enum, so state types can be shared between machines.
+pub(crate) fn derive_state_machine2(item: TokenStream) -> Result<TokenStream> {
+    let item = parse2::<ItemEnum>(item)?;
+    let item_ident = &item.ident;
+    let mut errors = Errors::default();
+    let ItemOptions { krate, hints } = errors.check(item_options(&item.attrs)).unwrap_or_default();
+    let table = errors.check(transition_table(&item.attrs)).flatten();
+
+    let mut taken = token_idents(item.to_token_stream());
+    let event_ty = fresh_ident("E", &mut taken);
+
+    let states = variant_patterns(&item).filter_map(|v| errors.check(v)).collect_vec();
+    let fallback = fallback_arm(states.len(), item.variants.len());
+
+    // the states each state can move to. Staying in the same state is always allowed
+    let mut allowed = states.iter().map(|s| (&s.variant.ident, vec![s])).collect_vec();
+    if let Some(table) = &table {
+        if !item.generics.params.is_empty() {
+            errors.push(Error::Other(item.generics.span(), "Transition tables are only supported by enums without generic parameters".into()));
+        }
+        let mut state = |ident: &Ident| {
+            let found = states.iter().find(|s| s.variant.ident == *ident);
+            if found.is_none() {
+                errors.push(Error::Other(ident.span(), format!("`{}` is not a state of `{}`", ident, item_ident)));
+            }
+            found
+        };
+        for Transitions { from, to } in table {
+            let from = state(from);
+            let to = to.iter().filter_map(&mut state).collect_vec();
+            let Some(from) = from else {
+                continue;
+            };
+            let (_, targets) = allowed.iter_mut().find(|(ident, _)| **ident == from.variant.ident).unwrap();
+            for to in to {
+                if !targets.iter().any(|s| s.variant.ident == to.variant.ident) {
+                    targets.push(to);
+                }
+            }
+        }
+    }
+
+    let (_, ty_generics, _) = item.generics.split_for_impl();
+    let mut generics = item.generics.clone();
+    generics.params.push(GenericParam::Type(TypeParam::from(event_ty.clone())));
+    let where_clause = generics.make_where_clause();
+    for ty in states.iter().map(|s| s.inner_ty).unique() {
+        where_clause.predicates.push(parse2(quote!(#ty : #krate::Step<#event_ty>))?);
+        let checked = table.is_some().then(|| quote!(+ #krate::TransitionFrom<Self, #ty>));
+        where_clause.predicates.push(parse2(quote! {
+            <#ty as #krate::Step<#event_ty>>::Next : ::core::convert::Into<Self> #checked
+        })?);
+    }
+    let (impl_generics, _, where_clause) = generics.split_for_impl();
+
+    let name_arms = item.variants.iter().map(|v| {
+        let var_ident = &v.ident;
+        let name = format!("{}::{}", item_ident, var_ident);
+        quote!(#item_ident::#var_ident { .. } => #name)
+    }).collect_vec();
+
+    let arms = states.iter().map(|VariantInfo { variant, pattern, .. }| {
+        let step = quote!(::core::convert::Into::into(#krate::Step::step(value, event)));
+        if table.is_none() {
+            return quote!(#pattern => #step);
+        }
+        let (_, targets) = allowed.iter().find(|(ident, _)| **ident == variant.ident).unwrap();
+        let targets = targets.iter().map(|s| {
+            let var_ident = &s.variant.ident;
+            quote!(#item_ident::#var_ident { .. })
+        });
+        let msg = format!("`{}::{}` is not allowed to move to `{{}}`", item_ident, variant.ident);
+        quote! {
+            #pattern => {
+                let next : Self = #step;
+                ::core::debug_assert!(::core::matches!(next, #(#targets)|*), #msg, match &next {
+                    #(#name_arms,)*
+                });
+                next
+            }
+        }
+    }).collect_vec();
+
+    let mut output = quote! {
+        impl #impl_generics #krate::Step<#event_ty> for #item_ident #ty_generics #where_clause {
+            type Next = Self;
+
+            fn step(self, event : #event_ty) -> Self {
+                match self {
+                    #(#arms,)*
+                    #fallback
+                }
+            }
+        }
+    };
+
+    if table.is_some() {
+        // the enum can hold any state, so returning it is only checked when stepping
+        let item_ty: Type = parse_quote!(#item_ident);
+        let mut impls = states.iter().map(|s| (s.inner_ty, &item_ty)).collect_vec();
+        for (from, targets) in &allowed {
+            let from = states.iter().find(|s| s.variant.ident == **from).unwrap();
+            impls.extend(targets.iter().map(|to| (from.inner_ty, to.inner_ty)));
+        }
+        // states sharing a type would otherwise implement the same transition twice
+        let impls = impls.into_iter().unique_by(|(from, to)| (normalize_type(from), normalize_type(to)));
+        for (from, to) in impls {
+            output.extend(quote! {
+                impl #krate::TransitionFrom<#item_ident, #from> for #to {}
+            });
+        }
+    }
+
+    Ok(errors.finish(hints.apply(output)?))
+}
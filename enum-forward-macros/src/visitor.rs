@@ -0,0 +1,118 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use proc_macro2::{TokenStream, TokenTree};
+use quote::{format_ident, quote, ToTokens};
+use syn::{Generics, Ident, ItemEnum, parse2, Type};
+
+use crate::common::{fallback_arm, method_hints, snake_case, variant_patterns, VariantInfo};
+use crate::error::{Error, Errors, Result};
+
+/// Generate a `{Enum}Visitor` trait with a method for each variant, defaulting to `visit_any`,
+/// and `accept` and `accept_mut` methods on the enum to call them
+pub(crate) fn derive_visitor2(item: TokenStream) -> Result<TokenStream> {
+    let item = parse2::<ItemEnum>(item)?;
+    let item_ident = &item.ident;
+    let vis = &item.vis;
+    let visitor_ident = format_ident!("{}Visitor", item_ident);
+    let generics = &item.generics;
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let mut errors = Errors::default();
+    let hints = errors.check(method_hints(&item.attrs)).unwrap_or_default();
+
+    let mut methods = vec![];
+    let mut arms = vec![];
+    let mut mut_arms = vec![];
+    for v in variant_patterns(&item) {
+        let Some(VariantInfo { variant, inner_ty, pattern }) = errors.check(v) else {
+            continue;
+        };
+        let name = snake_case(&variant.ident.to_string());
+        if name == "any" {
+            errors.push(Error::Other(variant.ident.span(), "`visit_any` is taken by the visitor's default method".into()));
+            continue;
+        }
+        let visit = format_ident!("visit_{}", name);
+        let visit_mut = format_ident!("visit_{}_mut", name);
+        let doc = format!(" Visit the value of `{}::{}`", item_ident, variant.ident);
+
+        // a value that may borrow can't be passed as `&dyn Any`, so its default does nothing
+        let (default, default_mut) = if static_type(inner_ty, &item.generics) {
+            (quote!(self.visit_any(value)), quote!(self.visit_any_mut(value)))
+        } else {
+            (quote!(let _ = value;), quote!(let _ = value;))
+        };
+        methods.push(quote! {
+            #[doc = #doc]
+            fn #visit(&mut self, value : &#inner_ty) {
+                #default
+            }
+
+            #[doc = #doc]
+            fn #visit_mut(&mut self, value : &mut #inner_ty) {
+                #default_mut
+            }
+        });
+        arms.push(quote!(#pattern => visitor.#visit(value)));
+        mut_arms.push(quote!(#pattern => visitor.#visit_mut(value)));
+    }
+    let fallback = fallback_arm(arms.len(), item.variants.len());
+
+    let output = quote! {
+        #vis trait #visitor_ident #generics #where_clause {
+            /// Visit the value of any variant without a method of its own
+            fn visit_any(&mut self, value : &dyn ::core::any::Any) {
+                let _ = value;
+            }
+
+            /// Visit the value of any variant without a method of its own, mutably
+            fn visit_any_mut(&mut self, value : &mut dyn ::core::any::Any) {
+                let _ = value;
+            }
+
+            #(#methods)*
+        }
+
+        impl #impl_generics #item_ident #ty_generics #where_clause {
+            /// Pass the inner value to the visitor's method for its variant
+            #vis fn accept(&self, visitor : &mut (impl #visitor_ident #ty_generics + ?Sized)) {
+                match self {
+                    #(#arms,)*
+                    #fallback
+                }
+            }
+
+            /// Pass the inner value to the visitor's method for its variant, mutably
+            #vis fn accept_mut(&mut self, visitor : &mut (impl #visitor_ident #ty_generics + ?Sized)) {
+                match self {
+                    #(#mut_arms,)*
+                    #fallback
+                }
+            }
+        }
+    };
+
+    Ok(errors.finish(hints.apply(output)?))
+}
+
+/// Check that a variant type names no lifetime but `'static` and none of the enum's type
+/// parameters, so its value is always `Any`
+fn static_type(ty: &Type, generics: &Generics) -> bool {
+    fn static_tokens(tokens: TokenStream, params: &[&Ident]) -> bool {
+        let mut lifetime = false;
+        tokens.into_iter().all(|tt| {
+            let is_static = match &tt {
+                TokenTree::Ident(i) if lifetime => i == "static",
+                TokenTree::Ident(i) => !params.contains(&i),
+                TokenTree::Group(g) => static_tokens(g.stream(), params),
+                _ => true,
+            };
+            lifetime = matches!(&tt, TokenTree::Punct(p) if p.as_char() == '\'');
+            is_static
+        })
+    }
+
+    let params = generics.type_params().map(|p| &p.ident).collect::<Vec<_>>();
+    static_tokens(ty.to_token_stream(), &params)
+}
@@ -0,0 +1,97 @@
+use std::hint::black_box;
+use criterion::{criterion_group, criterion_main, Criterion};
+use enum_forward::{forwardable, Forward, From};
+
+#[forwardable]
+trait Area {
+    fn area(&self) -> f64;
+}
+
+struct Circle { radius: f64 }
+struct Square { side: f64 }
+struct Triangle { base: f64, height: f64 }
+
+impl Area for Circle {
+    fn area(&self) -> f64 { std::f64::consts::PI * self.radius * self.radius }
+}
+
+impl Area for Square {
+    fn area(&self) -> f64 { self.side * self.side }
+}
+
+impl Area for Triangle {
+    fn area(&self) -> f64 { self.base * self.height / 2.0 }
+}
+
+#[derive(Forward, From)]
+#[forward(Area)]
+enum Shape {
+    Circle(Circle),
+    Square(Square),
+    Triangle(Triangle),
+}
+
+#[derive(Forward, From)]
+#[forward(Area)]
+#[enum_forward(inline = always)]
+enum InlinedShape {
+    Circle(Circle),
+    Square(Square),
+    Triangle(Triangle),
+}
+
+#[derive(Forward, From)]
+#[forward(Area)]
+#[enum_forward(inline = never)]
+enum OutlinedShape {
+    Circle(Circle),
+    Square(Square),
+    Triangle(Triangle),
+}
+
+fn shapes<T>(make: impl Fn(usize) -> T) -> Vec<T> {
+    (0..1024).map(make).collect()
+}
+
+fn circle(i: usize) -> Circle { Circle { radius: i as f64 } }
+fn square(i: usize) -> Square { Square { side: i as f64 } }
+fn triangle(i: usize) -> Triangle { Triangle { base: i as f64, height: 2.0 } }
+
+fn dispatch(c: &mut Criterion) {
+    let mut group = c.benchmark_group("area");
+
+    let enums = shapes(|i| match i % 3 {
+        0 => Shape::from(circle(i)),
+        1 => Shape::from(square(i)),
+        _ => Shape::from(triangle(i)),
+    });
+    group.bench_function("enum", |b| b.iter(|| black_box(&enums).iter().map(Area::area).sum::<f64>()));
+
+    let inlined = shapes(|i| match i % 3 {
+        0 => InlinedShape::from(circle(i)),
+        1 => InlinedShape::from(square(i)),
+        _ => InlinedShape::from(triangle(i)),
+    });
+    group.bench_function("enum inline(always)", |b| b.iter(|| black_box(&inlined).iter().map(Area::area).sum::<f64>()));
+
+    let outlined = shapes(|i| match i % 3 {
+        0 => OutlinedShape::from(circle(i)),
+        1 => OutlinedShape::from(square(i)),
+        _ => OutlinedShape::from(triangle(i)),
+    });
+    group.bench_function("enum inline(never)", |b| b.iter(|| black_box(&outlined).iter().map(Area::area).sum::<f64>()));
+
+    let boxed = shapes(|i| -> Box<dyn Area> {
+        match i % 3 {
+            0 => Box::new(circle(i)),
+            1 => Box::new(square(i)),
+            _ => Box::new(triangle(i)),
+        }
+    });
+    group.bench_function("dyn", |b| b.iter(|| black_box(&boxed).iter().map(|s| s.area()).sum::<f64>()));
+
+    group.finish();
+}
+
+criterion_group!(benches, dispatch);
+criterion_main!(benches);
@@ -1,17 +1,408 @@
-use std::marker::PhantomData;
+#![no_std]
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
 pub use enum_forward_macros::*;
 
+#[cfg(feature = "serde")]
+#[doc(hidden)]
+pub mod serde_impl;
+
+/// Macros implementing the standard library traits forwarded by their bare names. Generated code
+/// glob imports them, so that a forwardable trait of the same name in scope takes precedence
+#[doc(hidden)]
+pub mod __builtin {
+    pub use enum_forward_macros::__forward_builtin as Display;
+    pub use enum_forward_macros::__forward_builtin as AsRef;
+    pub use enum_forward_macros::__forward_builtin as AsMut;
+    pub use enum_forward_macros::__forward_builtin as Iterator;
+    pub use enum_forward_macros::__forward_builtin as DoubleEndedIterator;
+    pub use enum_forward_macros::__forward_builtin as ExactSizeIterator;
+    pub use enum_forward_macros::__forward_builtin as Index;
+    pub use enum_forward_macros::__forward_builtin as IndexMut;
+    pub use enum_forward_macros::__forward_builtin as Error;
+    pub use enum_forward_macros::__forward_builtin as Read;
+    pub use enum_forward_macros::__forward_builtin as Write;
+    pub use enum_forward_macros::__forward_builtin as Neg;
+    pub use enum_forward_macros::__forward_builtin as Not;
+    pub use enum_forward_macros::__forward_builtin as Add;
+    pub use enum_forward_macros::__forward_builtin as Sub;
+    pub use enum_forward_macros::__forward_builtin as Mul;
+    pub use enum_forward_macros::__forward_builtin as Div;
+    pub use enum_forward_macros::__forward_builtin as Rem;
+    pub use enum_forward_macros::__forward_builtin as BitAnd;
+    pub use enum_forward_macros::__forward_builtin as BitOr;
+    pub use enum_forward_macros::__forward_builtin as BitXor;
+    pub use enum_forward_macros::__forward_builtin as Shl;
+    pub use enum_forward_macros::__forward_builtin as Shr;
+    pub use enum_forward_macros::__forward_builtin as AddAssign;
+    pub use enum_forward_macros::__forward_builtin as SubAssign;
+    pub use enum_forward_macros::__forward_builtin as MulAssign;
+    pub use enum_forward_macros::__forward_builtin as DivAssign;
+    pub use enum_forward_macros::__forward_builtin as RemAssign;
+    pub use enum_forward_macros::__forward_builtin as BitAndAssign;
+    pub use enum_forward_macros::__forward_builtin as BitOrAssign;
+    pub use enum_forward_macros::__forward_builtin as BitXorAssign;
+    pub use enum_forward_macros::__forward_builtin as ShlAssign;
+    pub use enum_forward_macros::__forward_builtin as ShrAssign;
+}
+
+/// Paths used by generated code that may not be nameable from the crate using it
+#[doc(hidden)]
+pub mod __private {
+    #[cfg(feature = "alloc")]
+    pub use alloc::boxed::Box;
+}
+
 pub trait Forward<I> {
     type Output;
     fn forward(&self, input : &I) -> Self::Output;
 }
 
+#[allow(dead_code)]
 struct EnumIterator<'a, I : Clone> {
     counter : usize,
     input : &'a I,
 }
 
+#[allow(dead_code)]
 trait ForwardIter<I : Clone> {
     type Output;
-    fn forward_iter(input: &I) -> EnumIterator<I>;
-}
\ No newline at end of file
+    fn forward_iter(input: &I) -> EnumIterator<'_, I>;
+}
+
+/// Like [Forward], but with the input borrowed mutably, so it can collect state as it's forwarded
+pub trait ForwardMut<I> {
+    type Output;
+    fn forward_mut(&self, input : &mut I) -> Self::Output;
+}
+
+/// Like [Forward], but with the input passed by value
+pub trait ForwardOwned<I> {
+    type Output;
+    fn forward_owned(&self, input : I) -> Self::Output;
+}
+
+/// Like [Forward], but each variant's inner value can have its own output type. Implemented by
+/// `#[derive(ForwardEach)]`, with the outputs wrapped in an enum with the same variants
+pub trait ForwardEach<I> {
+    type Output;
+    fn forward_each(&self, input : &I) -> Self::Output;
+}
+
+/// Visits the inner value of an enum. Unlike [Forward], this is implemented by the visitor for
+/// each type it accepts, so a visitor can be implemented generically over the inner types
+pub trait Visitor<T : ?Sized> {
+    type Output;
+    fn visit(self, value : &T) -> Self::Output;
+}
+
+/// Passes the inner value of an enum to a [Visitor]. Implemented by `#[derive(Forward)]`
+pub trait Dispatch<V> {
+    type Output;
+    fn dispatch(&self, visitor : V) -> Self::Output;
+}
+
+/// Like [Visitor], but with mutable access to the inner value
+pub trait VisitorMut<T : ?Sized> {
+    type Output;
+    fn visit_mut(self, value : &mut T) -> Self::Output;
+}
+
+/// Passes the inner value of an enum to a [VisitorMut]. Implemented by `#[derive(Forward)]`
+pub trait DispatchMut<V> {
+    type Output;
+    fn dispatch_mut(&mut self, visitor : V) -> Self::Output;
+}
+
+/// Transforms the inner value of an enum, keeping its variant. Implemented by the mapper for each
+/// type it accepts, like [Visitor]
+pub trait Mapper<T> {
+    fn map(self, value : T) -> T;
+}
+
+/// Transforms the inner value of an enum, keeping its variant, or fails with `Error`
+pub trait TryMapper<T> {
+    type Error;
+    fn try_map(self, value : T) -> Result<T, Self::Error>;
+}
+
+/// Closures are mappers for the one type they accept, so enums whose variants all hold the same
+/// type can be mapped with a closure
+impl<T, F> Mapper<T> for F where F : FnOnce(T) -> T {
+    #[inline]
+    fn map(self, value : T) -> T {
+        self(value)
+    }
+}
+
+impl<T, E, F> TryMapper<T> for F where F : FnOnce(T) -> Result<T, E> {
+    type Error = E;
+
+    #[inline]
+    fn try_map(self, value : T) -> Result<T, E> {
+        self(value)
+    }
+}
+
+/// Passes the inner value of an enum to a [Mapper], wrapping the result in the same variant.
+/// Implemented by `#[derive(Forward)]` for enums
+pub trait MapInner<M> : Sized {
+    fn map_inner(self, mapper : M) -> Self;
+}
+
+/// Passes the inner value of an enum to a [TryMapper], wrapping the result in the same variant.
+/// Implemented by `#[derive(Forward)]` for enums
+pub trait TryMapInner<M> : Sized {
+    type Error;
+    fn try_map_inner(self, mapper : M) -> Result<Self, Self::Error>;
+}
+
+/// Visits the inner values of two enums at once. Passed to [dispatch_pair], which calls it with
+/// every combination of their variants
+pub trait PairVisitor<A : ?Sized, B : ?Sized> {
+    type Output;
+    fn visit_pair(self, first : &A, second : &B) -> Self::Output;
+}
+
+/// Pass the inner values of two enums deriving `Forward` to a [PairVisitor]. The visitor needs
+/// to accept every pair of their inner types
+pub fn dispatch_pair<A, B, V, R>(first : &A, second : &B, visitor : V) -> R
+    where A : ?Sized + for<'b> Dispatch<First<'b, B, V>, Output=R>, B : ?Sized {
+    first.dispatch(First { second, visitor })
+}
+
+/// Visits the first value of a pair for [dispatch_pair], then dispatches on the second
+#[doc(hidden)]
+pub struct First<'b, B : ?Sized, V> {
+    second : &'b B,
+    visitor : V,
+}
+
+impl<'b, A, B, V, R> Visitor<A> for First<'b, B, V>
+    where A : ?Sized, B : ?Sized + for<'a> Dispatch<Second<'a, A, V>, Output=R> {
+    type Output = R;
+
+    fn visit(self, value : &A) -> R {
+        self.second.dispatch(Second { first : value, visitor : self.visitor })
+    }
+}
+
+/// Visits the second value of a pair for [dispatch_pair], holding on to the first
+#[doc(hidden)]
+pub struct Second<'a, A : ?Sized, V> {
+    first : &'a A,
+    visitor : V,
+}
+
+impl<'a, A, B, V> Visitor<B> for Second<'a, A, V> where A : ?Sized, B : ?Sized, V : PairVisitor<A, B> {
+    type Output = V::Output;
+
+    fn visit(self, value : &B) -> V::Output {
+        self.visitor.visit_pair(self.first, value)
+    }
+}
+
+/// Moves a state to the next state on an event. Implemented by `#[derive(StateMachine)]` for
+/// enums of states that implement it themselves, returning a state that converts into the enum
+pub trait Step<E> {
+    type Next;
+    fn step(self, event : E) -> Self::Next;
+}
+
+/// Marks a state as one that `S` is allowed to move to in the state machine `M`. Implemented by
+/// `#[derive(StateMachine)]` from the table in `#[state_machine(transitions(...))]`, so that
+/// stepping to any other state doesn't compile. States that step to `M` itself can hold any state,
+/// so those transitions are only checked with debug assertions.
+pub trait TransitionFrom<M, S> {}
+
+/// The error from a binary operator forwarded by an enum, when its operands are different
+/// variants. Returned instead of panicking with `#[forward_ops(mismatch = error)]`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VariantMismatch {
+    pub left : &'static str,
+    pub right : &'static str,
+}
+
+impl core::fmt::Display for VariantMismatch {
+    fn fmt(&self, f : &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "mismatched variants `{}` and `{}`", self.left, self.right)
+    }
+}
+
+impl core::error::Error for VariantMismatch {}
+
+impl<I, R, T> Forward<I> for Option<T> where T : Forward<I, Output=R> {
+    type Output = Option<R>;
+
+    fn forward(&self, input : &I) -> Option<R> {
+        self.as_ref().map(|value| value.forward(input))
+    }
+}
+
+impl<I, R, T, E> Forward<I> for Result<T, E> where T : Forward<I, Output=R>, E : Forward<I, Output=R> {
+    type Output = R;
+
+    fn forward(&self, input : &I) -> R {
+        match self {
+            Ok(value) => value.forward(input),
+            Err(value) => value.forward(input),
+        }
+    }
+}
+
+#[cfg(feature = "either")]
+impl<I, R, A, B> Forward<I> for either::Either<A, B> where A : Forward<I, Output=R>, B : Forward<I, Output=R> {
+    type Output = R;
+
+    fn forward(&self, input : &I) -> R {
+        match self {
+            either::Either::Left(value) => value.forward(input),
+            either::Either::Right(value) => value.forward(input),
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<I, T> Forward<I> for alloc::boxed::Box<T> where T : Forward<I> + ?Sized {
+    type Output = T::Output;
+
+    fn forward(&self, input : &I) -> T::Output {
+        (**self).forward(input)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<I, T> Forward<I> for alloc::rc::Rc<T> where T : Forward<I> + ?Sized {
+    type Output = T::Output;
+
+    fn forward(&self, input : &I) -> T::Output {
+        (**self).forward(input)
+    }
+}
+
+#[cfg(all(feature = "alloc", target_has_atomic = "ptr"))]
+impl<I, T> Forward<I> for alloc::sync::Arc<T> where T : Forward<I> + ?Sized {
+    type Output = T::Output;
+
+    fn forward(&self, input : &I) -> T::Output {
+        (**self).forward(input)
+    }
+}
+
+impl<I, R, T> ForwardMut<I> for Option<T> where T : ForwardMut<I, Output=R> {
+    type Output = Option<R>;
+
+    fn forward_mut(&self, input : &mut I) -> Option<R> {
+        self.as_ref().map(|value| value.forward_mut(input))
+    }
+}
+
+impl<I, R, T, E> ForwardMut<I> for Result<T, E> where T : ForwardMut<I, Output=R>, E : ForwardMut<I, Output=R> {
+    type Output = R;
+
+    fn forward_mut(&self, input : &mut I) -> R {
+        match self {
+            Ok(value) => value.forward_mut(input),
+            Err(value) => value.forward_mut(input),
+        }
+    }
+}
+
+#[cfg(feature = "either")]
+impl<I, R, A, B> ForwardMut<I> for either::Either<A, B> where A : ForwardMut<I, Output=R>, B : ForwardMut<I, Output=R> {
+    type Output = R;
+
+    fn forward_mut(&self, input : &mut I) -> R {
+        match self {
+            either::Either::Left(value) => value.forward_mut(input),
+            either::Either::Right(value) => value.forward_mut(input),
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<I, T> ForwardMut<I> for alloc::boxed::Box<T> where T : ForwardMut<I> + ?Sized {
+    type Output = T::Output;
+
+    fn forward_mut(&self, input : &mut I) -> T::Output {
+        (**self).forward_mut(input)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<I, T> ForwardMut<I> for alloc::rc::Rc<T> where T : ForwardMut<I> + ?Sized {
+    type Output = T::Output;
+
+    fn forward_mut(&self, input : &mut I) -> T::Output {
+        (**self).forward_mut(input)
+    }
+}
+
+#[cfg(all(feature = "alloc", target_has_atomic = "ptr"))]
+impl<I, T> ForwardMut<I> for alloc::sync::Arc<T> where T : ForwardMut<I> + ?Sized {
+    type Output = T::Output;
+
+    fn forward_mut(&self, input : &mut I) -> T::Output {
+        (**self).forward_mut(input)
+    }
+}
+
+impl<I, R, T> ForwardOwned<I> for Option<T> where T : ForwardOwned<I, Output=R> {
+    type Output = Option<R>;
+
+    fn forward_owned(&self, input : I) -> Option<R> {
+        self.as_ref().map(|value| value.forward_owned(input))
+    }
+}
+
+impl<I, R, T, E> ForwardOwned<I> for Result<T, E> where T : ForwardOwned<I, Output=R>, E : ForwardOwned<I, Output=R> {
+    type Output = R;
+
+    fn forward_owned(&self, input : I) -> R {
+        match self {
+            Ok(value) => value.forward_owned(input),
+            Err(value) => value.forward_owned(input),
+        }
+    }
+}
+
+#[cfg(feature = "either")]
+impl<I, R, A, B> ForwardOwned<I> for either::Either<A, B> where A : ForwardOwned<I, Output=R>, B : ForwardOwned<I, Output=R> {
+    type Output = R;
+
+    fn forward_owned(&self, input : I) -> R {
+        match self {
+            either::Either::Left(value) => value.forward_owned(input),
+            either::Either::Right(value) => value.forward_owned(input),
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<I, T> ForwardOwned<I> for alloc::boxed::Box<T> where T : ForwardOwned<I> + ?Sized {
+    type Output = T::Output;
+
+    fn forward_owned(&self, input : I) -> T::Output {
+        (**self).forward_owned(input)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<I, T> ForwardOwned<I> for alloc::rc::Rc<T> where T : ForwardOwned<I> + ?Sized {
+    type Output = T::Output;
+
+    fn forward_owned(&self, input : I) -> T::Output {
+        (**self).forward_owned(input)
+    }
+}
+
+#[cfg(all(feature = "alloc", target_has_atomic = "ptr"))]
+impl<I, T> ForwardOwned<I> for alloc::sync::Arc<T> where T : ForwardOwned<I> + ?Sized {
+    type Output = T::Output;
+
+    fn forward_owned(&self, input : I) -> T::Output {
+        (**self).forward_owned(input)
+    }
+}
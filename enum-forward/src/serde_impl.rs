@@ -0,0 +1,42 @@
+//! Support code for `#[derive(ForwardSerialize, ForwardDeserialize)]`
+
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt::Write;
+
+pub use serde;
+use serde::de::{Deserialize, Deserializer, Error};
+use serde_value::Value;
+
+/// The input of a `ForwardDeserialize` impl, buffered so that each variant's type can be tried
+pub struct Attempts {
+    value : Value,
+    errors : Vec<(&'static str, String)>,
+}
+
+impl Attempts {
+    pub fn new<'de, D : Deserializer<'de>>(deserializer : D) -> Result<Self, D::Error> {
+        Ok(Attempts { value : Value::deserialize(deserializer)?, errors : Vec::new() })
+    }
+
+    /// Try to deserialize the input as `T`, keeping the error if it fails
+    pub fn attempt<'de, T : Deserialize<'de>>(&mut self, variant : &'static str) -> Option<T> {
+        match T::deserialize(self.value.clone()) {
+            Ok(value) => Some(value),
+            Err(err) => {
+                self.errors.push((variant, err.to_string()));
+                None
+            }
+        }
+    }
+
+    /// The error for input that none of the variants matched, listing why each one failed
+    pub fn fail<E : Error>(self, item : &str) -> E {
+        let mut msg = format!("data did not match any variant of `{}`", item);
+        for (variant, err) in &self.errors {
+            let _ = write!(msg, "\n    {}: {}", variant, err);
+        }
+        E::custom(msg)
+    }
+}
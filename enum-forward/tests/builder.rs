@@ -1,6 +1,4 @@
-use std::process::Output;
-use enum_forward::{Forward, From, TryInto};
-use enum_forward_macros::forward_to;
+use enum_forward::Forward;
 
 struct A {}
 struct B {}
@@ -25,11 +23,11 @@ trait FooForwarder<R> {
 }
 
 impl Foo {
-    fn forward<'a, D: FooForwarder<R>, R>(&self, fwd: D) -> R {
-        return match self {
+    fn forward<D: FooForwarder<R>, R>(&self, fwd: D) -> R {
+        match self {
             Foo::A(a) => { fwd.build()(a) }
             Foo::B(b) => { fwd.build()(b) }
-        };
+        }
     }
 }
 
@@ -42,7 +40,7 @@ impl<I, R> Visit<I> for Foo where A : Visit<I, Output=R>, B : Visit<I, Output=R>
     type Output = R;
 
     fn visit(&self, input: I) -> R {
-        return match self {
+        match self {
             Foo::A(val) => {Visit::visit(val, input)}
             Foo::B(val) => {Visit::visit(val, input)}
         }
@@ -55,7 +53,7 @@ struct GetNameFwd {}
 
 impl<T> Visit<GetNameFwd> for T where T : GetName {
     type Output = &'static str;
-    fn visit(&self, input: GetNameFwd) -> &'static str {
+    fn visit(&self, _input: GetNameFwd) -> &'static str {
         self.name()
     }
 }
@@ -66,6 +64,7 @@ impl Foo {
     }
 }
 
+#[allow(dead_code, clippy::needless_lifetimes)]
 trait Bar {
     fn bar<'a>(self, name : &'a str);
 }
@@ -73,21 +72,37 @@ trait Bar {
 impl Bar for Foo {
     // #[forward_to(Foo as GetName)]
     // fn bar<'a>(self, name : &'a str);
-        fn bar<'a>(self, name: &'a str) {
-        struct barVisitor<'a, '_blanket> {
+    #[allow(clippy::needless_lifetimes)]
+    fn bar<'a>(self, _name: &'a str) {
+        #[allow(dead_code)]
+        struct BarVisitor<'a, '_blanket> {
             name: &'a str,
             _phantom: std::marker::PhantomData<&'_blanket i32>,
         }
-        impl<'a, '_blanket, B> enum_forward::Forward<barVisitor<'a, '_blanket>> for B
+        impl<'a, '_blanket, B> Visit<BarVisitor<'a, '_blanket>> for B
         where
             B: GetName,
         {
             type Output = ();
 
-            fn forward(&self, input: &barVisitor<'a, '_blanket>) -> Self::Output {
+            fn visit(&self, _input: BarVisitor<'a, '_blanket>) -> Self::Output {
                 todo!()
             }
         }
     }
+}
 
-}
\ No newline at end of file
+struct NameForwarder {}
+
+impl FooForwarder<&'static str> for NameForwarder {
+    fn build<T: GetName>(&self) -> fn(&T) -> &'static str {
+        T::name
+    }
+}
+
+#[test]
+fn visit() {
+    assert_eq!(Foo::A(A {}).get_name(), "A");
+    assert_eq!(Foo::B(B {}).get_name(), "B");
+    assert_eq!(Foo::B(B {}).forward(NameForwarder {}), "B");
+}
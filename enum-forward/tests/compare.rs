@@ -0,0 +1,88 @@
+use std::cmp::Ordering;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use enum_forward::{ForwardEq, ForwardHash, ForwardOrd, ForwardPartialEq, ForwardPartialOrd};
+
+#[derive(Debug, ForwardPartialEq, ForwardEq, ForwardPartialOrd, ForwardOrd, ForwardHash)]
+enum Value {
+    Number(u32),
+    Text { text: String },
+    #[enum_forward(skip)]
+    Empty,
+}
+
+trait Priority {
+    fn priority(&self) -> u8;
+}
+
+#[derive(Debug)]
+struct Urgent;
+#[derive(Debug)]
+struct Routine(u8);
+
+impl Priority for Urgent {
+    fn priority(&self) -> u8 { 10 }
+}
+
+impl Priority for Routine {
+    fn priority(&self) -> u8 { self.0 }
+}
+
+#[derive(Debug, ForwardPartialEq, ForwardEq, ForwardPartialOrd, ForwardOrd, ForwardHash)]
+#[forward_cmp(key = Priority::priority)]
+enum Task<T: Priority> {
+    Urgent(Urgent),
+    Routine(Routine),
+    Other(Option<T>),
+}
+
+impl<T: Priority> Priority for Option<T> {
+    fn priority(&self) -> u8 { self.as_ref().map_or(0, T::priority) }
+}
+
+fn hash(value: &impl Hash) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[test]
+fn declaration_order() {
+    assert_eq!(Value::Number(1), Value::Number(1));
+    assert_ne!(Value::Number(1), Value::Number(2));
+    assert_ne!(Value::Number(1), Value::Text { text: "1".into() });
+    assert_eq!(Value::Empty, Value::Empty);
+
+    assert!(Value::Number(2) < Value::Number(3));
+    // variants are ordered by declaration before their values
+    assert!(Value::Number(100) < Value::Text { text: "a".into() });
+    assert!(Value::Text { text: "z".into() } < Value::Empty);
+    assert_eq!(Value::Empty.cmp(&Value::Empty), Ordering::Equal);
+
+    assert_eq!(hash(&Value::Number(1)), hash(&Value::Number(1)));
+    assert_ne!(hash(&Value::Number(1)), hash(&Value::Text { text: "1".into() }));
+}
+
+#[test]
+fn bare_values() {
+    assert!(Value::Number(1) == 1);
+    assert!(Value::Number(1) != 2);
+    assert!(Value::Text { text: "text".into() } == "text".to_string());
+    assert!(Value::Number(1) != "1".to_string());
+
+    assert!(Value::Number(1) < 2);
+    assert!(Value::Text { text: "a".into() } > 100);
+    assert!(Value::Number(100) < "a".to_string());
+}
+
+#[test]
+fn key_order() {
+    let urgent: Task<Routine> = Task::Urgent(Urgent);
+    assert_eq!(urgent, Task::Routine(Routine(10)));
+    assert!(Task::<Routine>::Routine(Routine(3)) < Task::Other(Some(Routine(4))));
+    assert!(Task::<Routine>::Other(None) < Task::Routine(Routine(1)));
+    assert_eq!(hash(&urgent), hash(&Task::<Routine>::Routine(Routine(10))));
+
+    assert!(urgent == Routine(10));
+    assert!(urgent > Routine(9));
+}
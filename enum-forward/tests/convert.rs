@@ -0,0 +1,52 @@
+use std::convert::TryInto;
+use enum_forward::{From, TryInto};
+
+#[derive(Debug, PartialEq, From, TryInto)]
+enum Value {
+    Text(String),
+    // the same type as `Text`, so it can't be converted from
+    #[enum_forward(skip)]
+    Name(std::string::String),
+    Bytes(Vec<u8>),
+    #[enum_forward(skip)]
+    Empty,
+    #[enum_forward(skip)]
+    Pair(u8, u8),
+}
+
+// types passed through `macro_rules` are wrapped in invisible groups
+macro_rules! value_enum {
+    ($name:ident, $($variant:ident($ty:ty)),*) => {
+        #[derive(From, TryInto)]
+        enum $name {
+            $($variant($ty)),*
+        }
+    };
+}
+
+value_enum!(Number, Small(u8), Large(u64));
+
+#[test]
+fn skip_variants() {
+    let text: Value = String::from("text").into();
+    assert_eq!(text, Value::Text("text".into()));
+    let text: String = text.try_into().unwrap();
+    assert_eq!(text, "text");
+
+    let name: Result<String, _> = Value::Name("name".into()).try_into();
+    assert_eq!(name, Err("Cannot convert Value::Name to String"));
+    let empty: Result<Vec<u8>, _> = Value::Empty.try_into();
+    assert!(empty.is_err());
+    let pair: Result<String, _> = Value::Pair(1, 2).try_into();
+    assert!(pair.is_err());
+}
+
+#[test]
+fn macro_generated() {
+    let small: Number = 1u8.into();
+    let large: Number = 2u64.into();
+    let small: u8 = small.try_into().unwrap();
+    let large: Result<u8, _> = large.try_into();
+    assert_eq!(small, 1);
+    assert!(large.is_err());
+}
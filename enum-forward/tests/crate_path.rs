@@ -0,0 +1,49 @@
+// the macros can be used through a re-export of enum-forward under another name
+mod facade {
+    pub use enum_forward as forward;
+}
+
+use facade::forward::{forwardable, Forward, From};
+
+#[forwardable(crate = facade::forward)]
+trait Describe {
+    fn describe(&self) -> String;
+}
+
+struct Len {}
+
+impl Forward<Len> for String {
+    type Output = usize;
+    fn forward(&self, _input: &Len) -> usize { self.len() }
+}
+
+impl Forward<Len> for Vec<u8> {
+    type Output = usize;
+    fn forward(&self, _input: &Len) -> usize { self.len() }
+}
+
+impl Describe for String {
+    fn describe(&self) -> String { format!("string {}", self) }
+}
+
+impl Describe for Vec<u8> {
+    fn describe(&self) -> String { format!("bytes {:?}", self) }
+}
+
+#[derive(From, Forward)]
+#[enum_forward(crate = facade::forward)]
+#[forward(Describe)]
+enum Value {
+    Text(String),
+    Bytes(Vec<u8>),
+}
+
+#[test]
+fn renamed_crate() {
+    let text: Value = String::from("abc").into();
+    let bytes: Value = vec![1u8, 2].into();
+    assert_eq!(text.forward(&Len {}), 3);
+    assert_eq!(bytes.forward(&Len {}), 2);
+    assert_eq!(text.describe(), "string abc");
+    assert_eq!(bytes.describe(), "bytes [1, 2]");
+}
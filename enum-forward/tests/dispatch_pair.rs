@@ -0,0 +1,78 @@
+use enum_forward::{dispatch_pair, Forward, PairVisitor};
+
+struct Circle { radius: f32 }
+struct Square { side: f32 }
+struct Point;
+
+trait Collide<B> {
+    fn collide(&self, other: &B) -> &'static str;
+}
+
+impl Collide<Circle> for Circle {
+    fn collide(&self, other: &Circle) -> &'static str {
+        if self.radius + other.radius > 1.0 { "circle-circle" } else { "miss" }
+    }
+}
+
+impl Collide<Square> for Circle {
+    fn collide(&self, _other: &Square) -> &'static str { "circle-square" }
+}
+
+impl Collide<Circle> for Square {
+    fn collide(&self, _other: &Circle) -> &'static str { "square-circle" }
+}
+
+impl Collide<Square> for Square {
+    fn collide(&self, other: &Square) -> &'static str {
+        if self.side == other.side { "same squares" } else { "square-square" }
+    }
+}
+
+impl Collide<Point> for Circle {
+    fn collide(&self, _other: &Point) -> &'static str { "circle-point" }
+}
+
+impl Collide<Point> for Square {
+    fn collide(&self, _other: &Point) -> &'static str { "square-point" }
+}
+
+#[derive(Forward)]
+enum Shape {
+    Circle(Circle),
+    Square(Square),
+}
+
+#[derive(Forward)]
+enum Target {
+    Circle(Circle),
+    Point(Point),
+}
+
+struct Collision;
+
+impl<A: Collide<B>, B> PairVisitor<A, B> for Collision {
+    type Output = &'static str;
+
+    fn visit_pair(self, first: &A, second: &B) -> &'static str {
+        first.collide(second)
+    }
+}
+
+#[test]
+fn every_pair() {
+    let circle = Shape::Circle(Circle { radius: 1.0 });
+    let square = Shape::Square(Square { side: 1.0 });
+
+    assert_eq!(dispatch_pair(&circle, &circle, Collision), "circle-circle");
+    assert_eq!(dispatch_pair(&circle, &square, Collision), "circle-square");
+    assert_eq!(dispatch_pair(&square, &circle, Collision), "square-circle");
+    assert_eq!(dispatch_pair(&square, &square, Collision), "same squares");
+}
+
+#[test]
+fn different_enums() {
+    let square = Shape::Square(Square { side: 2.0 });
+    assert_eq!(dispatch_pair(&square, &Target::Point(Point), Collision), "square-point");
+    let circle = Target::Circle(Circle { radius: 0.1 });
+    assert_eq!(dispatch_pair(&square, &circle, Collision), "square-circle");
+}
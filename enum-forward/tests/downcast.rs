@@ -0,0 +1,75 @@
+use std::any::TypeId;
+use enum_forward::Forward;
+
+#[derive(Forward)]
+#[forward(Any)]
+enum Value {
+    Int(i32),
+    Text(String),
+    Other { value: i32 },
+}
+
+#[derive(Forward)]
+#[forward(core::any::Any)]
+struct Wrapper<T: 'static> {
+    #[forward]
+    inner: T,
+    _tag: u8,
+}
+
+#[test]
+fn test_downcast_ref() {
+    let value = Value::Text("hello".to_string());
+    assert!(value.is::<String>());
+    assert!(!value.is::<i32>());
+    assert_eq!(value.downcast_ref::<String>().map(String::as_str), Some("hello"));
+    assert_eq!(value.downcast_ref::<i32>(), None);
+}
+
+#[test]
+fn test_same_type_variants() {
+    // unlike TryInto, variants can share a type
+    assert_eq!(Value::Int(1).downcast_ref::<i32>(), Some(&1));
+    assert_eq!(Value::Other { value: 2 }.downcast_ref::<i32>(), Some(&2));
+}
+
+#[test]
+fn test_downcast_mut() {
+    let mut value = Value::Int(1);
+    *value.downcast_mut::<i32>().unwrap() += 1;
+    assert_eq!(value.downcast_ref::<i32>(), Some(&2));
+    assert!(value.downcast_mut::<String>().is_none());
+}
+
+#[test]
+fn test_inner_type_id() {
+    assert_eq!(Value::Int(1).inner_type_id(), TypeId::of::<i32>());
+    assert_eq!(Value::Text(String::new()).inner_type_id(), TypeId::of::<String>());
+    assert_eq!(Value::Int(1).as_any().type_id(), TypeId::of::<i32>());
+}
+
+#[test]
+fn test_generic_struct() {
+    let wrapper = Wrapper { inner: 5u64, _tag: 0 };
+    assert_eq!(wrapper.downcast_ref::<u64>(), Some(&5));
+    assert_eq!(wrapper.inner_type_id(), TypeId::of::<u64>());
+}
+
+#[derive(Forward)]
+#[forward(Any)]
+enum Nested {
+    #[forward(flatten)]
+    Value(Value),
+    Float(f64),
+}
+
+#[test]
+fn test_flatten() {
+    let mut nested = Nested::Value(Value::Int(1));
+    assert!(nested.is::<i32>());
+    assert!(!nested.is::<Value>());
+    *nested.downcast_mut::<i32>().unwrap() += 1;
+    assert_eq!(nested.downcast_ref::<i32>(), Some(&2));
+    assert_eq!(nested.inner_type_id(), TypeId::of::<i32>());
+    assert_eq!(Nested::Float(1.0).downcast_ref::<f64>(), Some(&1.0));
+}
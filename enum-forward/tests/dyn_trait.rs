@@ -0,0 +1,83 @@
+use std::fmt::Display;
+use enum_forward::Forward;
+
+trait GetName {
+    fn name(&self) -> String;
+    fn rename(&mut self, name: &str);
+}
+
+struct Person { name: String }
+struct Robot { id: u32 }
+
+impl GetName for Person {
+    fn name(&self) -> String { self.name.clone() }
+    fn rename(&mut self, name: &str) { self.name = name.to_string() }
+}
+
+impl GetName for Robot {
+    fn name(&self) -> String { format!("robot {}", self.id) }
+    fn rename(&mut self, _name: &str) {}
+}
+
+#[derive(Forward)]
+#[forward(dyn(GetName), dyn(Display))]
+enum Named {
+    Person(Person),
+    Robot(Robot),
+}
+
+impl Display for Person {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result { write!(f, "{}", self.name) }
+}
+
+impl Display for Robot {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result { write!(f, "#{}", self.id) }
+}
+
+#[test]
+fn test_as_dyn() {
+    let named = [Named::Person(Person { name: "Ann".to_string() }), Named::Robot(Robot { id: 3 })];
+    let names = named.iter().map(Named::as_dyn_get_name).map(|n| n.name()).collect::<Vec<_>>();
+    assert_eq!(names, ["Ann", "robot 3"]);
+    assert_eq!(named[1].as_dyn_display().to_string(), "#3");
+}
+
+#[test]
+fn test_as_dyn_mut() {
+    let mut named = Named::Person(Person { name: "Ann".to_string() });
+    named.as_dyn_get_name_mut().rename("Bob");
+    assert_eq!(named.as_dyn_get_name().name(), "Bob");
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_into_box_dyn() {
+    let boxed: Vec<Box<dyn GetName>> = vec![
+        Named::Robot(Robot { id: 1 }).into_box_dyn_get_name(),
+        Named::Person(Person { name: "Cy".to_string() }).into_box_dyn_get_name(),
+    ];
+    assert_eq!(boxed.iter().map(|n| n.name()).collect::<Vec<_>>(), ["robot 1", "Cy"]);
+}
+
+#[derive(Forward)]
+#[forward(dyn(GetName))]
+enum Group {
+    #[forward(flatten)]
+    Named(Named),
+    Robot(Robot),
+}
+
+#[test]
+fn test_flatten() {
+    let mut group = Group::Named(Named::Person(Person { name: "Ann".to_string() }));
+    group.as_dyn_get_name_mut().rename("Dee");
+    assert_eq!(group.as_dyn_get_name().name(), "Dee");
+    assert_eq!(Group::Robot(Robot { id: 4 }).as_dyn_get_name().name(), "robot 4");
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_flatten_into_box_dyn() {
+    let boxed = Group::Named(Named::Robot(Robot { id: 2 })).into_box_dyn_get_name();
+    assert_eq!(boxed.name(), "robot 2");
+}
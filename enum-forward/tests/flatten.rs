@@ -0,0 +1,108 @@
+use std::convert::TryInto;
+use enum_forward::{From, TryInto};
+
+mod shapes {
+    use enum_forward::{From, TryInto};
+
+    #[derive(Debug, PartialEq)]
+    pub struct Circle(pub f32);
+
+    #[derive(Debug, PartialEq)]
+    pub struct Square(pub f32);
+
+    #[derive(Debug, PartialEq, From, TryInto)]
+    #[from(register)]
+    pub enum Shape {
+        Circle(Circle),
+        Square { square: Square },
+        #[enum_forward(skip)]
+        Empty,
+    }
+}
+
+use shapes::{Circle, Shape, Square};
+
+#[derive(Debug, PartialEq, From, TryInto)]
+enum Item {
+    #[from(flatten)]
+    Shape(Shape),
+    Label(String),
+}
+
+#[derive(Debug, PartialEq, From, TryInto)]
+enum Wrapper<T> {
+    Label(String),
+    #[from(flatten)]
+    Nested(Generic<u8, T>),
+}
+
+#[derive(Debug, PartialEq, From, TryInto)]
+#[from(register)]
+enum Generic<A, B> {
+    Vec(Vec<A>),
+    Option(Option<B>),
+}
+
+#[test]
+fn flatten_from() {
+    assert_eq!(Item::from(Circle(1.0)), Item::Shape(Shape::Circle(Circle(1.0))));
+    assert_eq!(Item::from(Square(2.0)), Item::Shape(Shape::Square { square: Square(2.0) }));
+    assert_eq!(Item::from(Shape::Empty), Item::Shape(Shape::Empty));
+    assert_eq!(Item::from("label".to_string()), Item::Label("label".into()));
+}
+
+#[test]
+fn flatten_tryinto() {
+    let circle: Result<Circle, _> = Item::from(Circle(1.0)).try_into();
+    assert_eq!(circle, Ok(Circle(1.0)));
+    let square: Result<Square, _> = Item::from(Circle(1.0)).try_into();
+    assert_eq!(square, Err("Cannot convert Shape::Circle to Square"));
+    let square: Result<Square, _> = Item::Shape(Shape::Empty).try_into();
+    assert_eq!(square, Err("Cannot convert Shape to Square"));
+    let circle: Result<Circle, _> = Item::Label("label".into()).try_into();
+    assert_eq!(circle, Err("Cannot convert Item::Label to Circle"));
+}
+
+#[test]
+fn flatten_generic() {
+    let vec: Wrapper<&str> = vec![1u8, 2].into();
+    assert_eq!(vec, Wrapper::Nested(Generic::Vec(vec![1, 2])));
+    let option: Wrapper<&str> = Some("text").into();
+    assert_eq!(option, Wrapper::Nested(Generic::Option(Some("text"))));
+
+    let vec: Result<Vec<u8>, _> = vec.try_into();
+    assert_eq!(vec, Ok(vec![1, 2]));
+    let option: Result<Option<&str>, _> = Wrapper::Label("label".into()).try_into();
+    assert_eq!(option, Err("Cannot convert Wrapper::Label to Option<T>"));
+}
+
+// flattened from another crate, whose variant types have to be in scope here
+mod foreign {
+    use std::convert::TryInto;
+    use enum_forward::{From, TryInto};
+    use enum_forward_registered::{Circle, Generic, Shape, Square};
+
+    #[derive(Debug, PartialEq, From, TryInto)]
+    enum Item<T> {
+        #[from(flatten)]
+        Shape(Shape),
+        #[from(flatten)]
+        Generic(Generic<u8, T>),
+        Label(String),
+    }
+
+    #[test]
+    fn flatten_foreign() {
+        let circle: Item<&str> = Circle(1.0).into();
+        assert_eq!(circle, Item::Shape(Shape::Circle(Circle(1.0))));
+        let option: Item<&str> = Some("text").into();
+        assert_eq!(option, Item::Generic(Generic::Option(Some("text"))));
+
+        let square: Result<Square, _> = circle.try_into();
+        assert_eq!(square, Err("Cannot convert Shape::Circle to Square"));
+        let option: Result<Option<&str>, _> = option.try_into();
+        assert_eq!(option, Ok(Some("text")));
+        let vec: Result<Vec<u8>, _> = Item::<&str>::Label("label".into()).try_into();
+        assert_eq!(vec, Err("Cannot convert Item::Label to Vec<u8>"));
+    }
+}
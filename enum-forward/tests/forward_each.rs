@@ -0,0 +1,52 @@
+use std::num::{ParseFloatError, ParseIntError};
+use enum_forward::{Forward, ForwardEach};
+
+struct Parse;
+
+struct IntText(&'static str);
+struct FloatText(&'static str);
+struct BoolText(&'static str);
+
+impl Forward<Parse> for IntText {
+    type Output = Result<i64, ParseIntError>;
+    fn forward(&self, _: &Parse) -> Self::Output { self.0.parse() }
+}
+
+impl Forward<Parse> for FloatText {
+    type Output = Result<f64, ParseFloatError>;
+    fn forward(&self, _: &Parse) -> Self::Output { self.0.parse() }
+}
+
+impl Forward<Parse> for BoolText {
+    type Output = bool;
+    fn forward(&self, _: &Parse) -> bool { self.0 == "true" }
+}
+
+#[derive(ForwardEach)]
+enum Literal {
+    Int(IntText),
+    Float { text: FloatText },
+    Bool(BoolText),
+}
+
+#[derive(ForwardEach)]
+#[forward_each(output = Parsed)]
+enum Number<T> {
+    Int(IntText),
+    Other(T),
+}
+
+#[test]
+fn forward_each() {
+    assert_eq!(Literal::Int(IntText("12")).forward_each(&Parse), LiteralOutput::Int(Ok(12)));
+    assert_eq!(Literal::Float { text: FloatText("0.5") }.forward_each(&Parse), LiteralOutput::Float(Ok(0.5)));
+    assert_eq!(Literal::Bool(BoolText("true")).forward_each(&Parse), LiteralOutput::Bool(true));
+    assert!(matches!(Literal::Int(IntText("x")).forward_each(&Parse), LiteralOutput::Int(Err(_))));
+}
+
+#[test]
+fn named_output() {
+    let parsed: Parsed<_, bool> = Number::<BoolText>::Int(IntText("3")).forward_each(&Parse);
+    assert_eq!(parsed, Parsed::Int(Ok(3)));
+    assert_eq!(Number::Other(BoolText("false")).forward_each(&Parse), Parsed::Other(false));
+}
@@ -0,0 +1,75 @@
+use enum_forward::{Forward, ForwardMut, ForwardOwned};
+
+struct Count {
+    words: usize,
+    bytes: usize,
+}
+
+struct Render(String);
+
+struct Text(String);
+struct Bytes(Vec<u8>);
+
+impl ForwardMut<Count> for Text {
+    type Output = ();
+
+    fn forward_mut(&self, count: &mut Count) {
+        count.words += self.0.split_whitespace().count();
+        count.bytes += self.0.len();
+    }
+}
+
+impl ForwardMut<Count> for Bytes {
+    type Output = ();
+
+    fn forward_mut(&self, count: &mut Count) {
+        count.bytes += self.0.len();
+    }
+}
+
+impl ForwardOwned<Render> for Text {
+    type Output = String;
+
+    fn forward_owned(&self, mut render: Render) -> String {
+        render.0.push_str(&self.0);
+        render.0
+    }
+}
+
+impl ForwardOwned<Render> for Bytes {
+    type Output = String;
+
+    fn forward_owned(&self, mut render: Render) -> String {
+        render.0.push_str(&format!("{:?}", self.0));
+        render.0
+    }
+}
+
+#[derive(Forward)]
+enum Value {
+    Text(Text),
+    Bytes(Bytes),
+    Either(Result<Text, Bytes>),
+}
+
+#[test]
+fn forward_mut() {
+    let values = [
+        Value::Text(Text("hello there".into())),
+        Value::Bytes(Bytes(vec![1, 2, 3])),
+        Value::Either(Err(Bytes(vec![]))),
+        Value::Either(Ok(Text("again".into()))),
+    ];
+    let mut count = Count { words: 0, bytes: 0 };
+    for value in &values {
+        value.forward_mut(&mut count);
+    }
+    assert_eq!((count.words, count.bytes), (3, 19));
+}
+
+#[test]
+fn forward_owned() {
+    let render = |value: &Value| value.forward_owned(Render("> ".into()));
+    assert_eq!(render(&Value::Text(Text("hi".into()))), "> hi");
+    assert_eq!(render(&Value::Bytes(Bytes(vec![7]))), "> [7]");
+}
@@ -0,0 +1,94 @@
+use enum_forward::{forwardable, Forward};
+
+#[forwardable]
+trait GetName {
+    fn name(&self) -> String;
+}
+
+struct Double {}
+
+impl Forward<Double> for u32 {
+    type Output = u32;
+    fn forward(&self, _input: &Double) -> u32 { self * 2 }
+}
+
+impl GetName for u32 {
+    fn name(&self) -> String { format!("u32 {}", self) }
+}
+
+#[cfg(feature = "std")]
+#[derive(Default)]
+struct Logger {
+    lines: Vec<String>,
+}
+
+#[cfg(feature = "std")]
+#[derive(Forward)]
+#[forward(io::Read, io::Write)]
+struct Logged<T>(#[forward] T, Logger);
+
+#[cfg(feature = "std")]
+#[derive(Forward)]
+#[forward(Read, Write)]
+enum Stream {
+    Buffer(std::io::Cursor<Vec<u8>>),
+    Empty(std::io::Empty),
+}
+
+#[derive(Forward)]
+#[forward(GetName)]
+struct Newtype(u32);
+
+#[derive(Forward)]
+#[forward(GetName)]
+struct Named {
+    #[forward]
+    inner: u32,
+    other: &'static str,
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn forward_tuple_struct() {
+    use std::io::{Cursor, Read, Write};
+
+    let mut logged = Logged(Cursor::new(vec![]), Logger::default());
+    logged.write_all(b"hello").unwrap();
+    logged.1.lines.push("wrote hello".into());
+
+    logged.0.set_position(0);
+    let mut read = String::new();
+    logged.read_to_string(&mut read).unwrap();
+    assert_eq!(read, "hello");
+    assert_eq!(logged.1.lines, vec!["wrote hello"]);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn forward_bare_io_traits() {
+    use std::io::{Read, Write};
+
+    let mut stream = Stream::Buffer(Default::default());
+    stream.write_all(b"hi").unwrap();
+    let mut read = String::new();
+    Stream::Buffer(std::io::Cursor::new(b"hi".to_vec())).read_to_string(&mut read).unwrap();
+    assert_eq!(read, "hi");
+
+    let mut empty = Stream::Empty(std::io::empty());
+    assert_eq!(empty.write(b"ignored").unwrap(), 7);
+    assert_eq!(empty.read(&mut [0; 4]).unwrap(), 0);
+}
+
+#[test]
+fn forward_newtype() {
+    assert_eq!(Newtype(3).forward(&Double {}), 6);
+    assert_eq!(Newtype(3).name(), "u32 3");
+}
+
+#[test]
+fn forward_named_field() {
+    let named = Named { inner: 4, other: "other" };
+    assert_eq!(named.forward(&Double {}), 8);
+    assert_eq!(named.name(), "u32 4");
+    assert_eq!(named.other, "other");
+}
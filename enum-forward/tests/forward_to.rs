@@ -0,0 +1,132 @@
+use std::fmt::Display;
+use enum_forward::{forward_to, Forward};
+
+trait GetName {
+    fn name(&self) -> &'static str;
+    fn is(&self, name: &str) -> bool {
+        self.name() == name
+    }
+}
+
+trait Counter {
+    fn count(&self) -> usize;
+    fn bump(&mut self, by: usize) -> usize;
+}
+
+struct A {}
+struct B {}
+
+struct Hits(usize);
+struct Misses(usize);
+
+impl Counter for Hits {
+    fn count(&self) -> usize { self.0 }
+    fn bump(&mut self, by: usize) -> usize {
+        self.0 += by;
+        self.0
+    }
+}
+
+impl Counter for Misses {
+    fn count(&self) -> usize { self.0 }
+    fn bump(&mut self, by: usize) -> usize {
+        self.0 += 2 * by;
+        self.0
+    }
+}
+
+impl GetName for A {
+    fn name(&self) -> &'static str { "A" }
+}
+
+impl GetName for B {
+    fn name(&self) -> &'static str { "B" }
+    fn is(&self, name: &str) -> bool { name.eq_ignore_ascii_case("b") }
+}
+
+#[derive(Forward)]
+enum Foo {
+    A(A),
+    B(B),
+}
+
+impl Foo {
+    // calls `is` on the variant
+    #[forward_to(Foo as GetName)]
+    fn is(&self, name: &str) -> bool;
+
+    // runs the body with `self` bound to the variant
+    #[forward_to(Foo as GetName)]
+    fn label<T: Display>(self, prefix: T, count: usize) -> String {
+        format!("{}{}{}", prefix, self.name(), count)
+    }
+}
+
+#[derive(Forward)]
+enum Stat {
+    Hits(Hits),
+    Misses(Misses),
+}
+
+impl Stat {
+    // calls `bump` on the variant with mutable access
+    #[forward_to(Stat as Counter)]
+    fn bump(&mut self, by: usize) -> usize;
+
+    // runs the body with `self` mutably bound to the variant
+    #[forward_to(Stat as Counter)]
+    fn double(&mut self) -> usize {
+        let by = self.count();
+        self.bump(by)
+    }
+}
+
+#[forward_to(stat: &mut Stat as Counter)]
+fn bump_twice(stat: &mut Stat) -> usize {
+    self.bump(1);
+    self.bump(1)
+}
+
+#[forward_to(value: &Foo as GetName)]
+fn shout(times: usize, value: &Foo) -> String {
+    self.name().repeat(times)
+}
+
+#[test]
+fn forward_method() {
+    assert!(Foo::A(A {}).is("A"));
+    assert!(!Foo::A(A {}).is("a"));
+    assert!(Foo::B(B {}).is("b"));
+}
+
+#[test]
+fn forward_body() {
+    assert_eq!(Foo::A(A {}).label("#", 1), "#A1");
+    assert_eq!(Foo::B(B {}).label('-', 2), "-B2");
+}
+
+#[test]
+fn forward_argument() {
+    assert_eq!(shout(3, &Foo::B(B {})), "BBB");
+}
+
+#[test]
+fn forward_mut_method() {
+    let mut stat = Stat::Hits(Hits(1));
+    assert_eq!(stat.bump(2), 3);
+    let mut stat = Stat::Misses(Misses(1));
+    assert_eq!(stat.bump(2), 5);
+}
+
+#[test]
+fn forward_mut_body() {
+    let mut stat = Stat::Hits(Hits(3));
+    assert_eq!(stat.double(), 6);
+    assert_eq!(stat.double(), 12);
+}
+
+#[test]
+fn forward_mut_argument() {
+    let mut stat = Stat::Misses(Misses(0));
+    assert_eq!(bump_twice(&mut stat), 4);
+}
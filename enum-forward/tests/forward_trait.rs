@@ -0,0 +1,192 @@
+use std::fmt::{Display, Formatter};
+use enum_forward::{forwardable, Forward};
+
+mod names {
+    use enum_forward::forwardable;
+
+    #[forwardable]
+    pub trait GetName {
+        fn name(&self) -> &'static str;
+
+        fn greet(&self, greeting: &str) -> String {
+            format!("{}, {}", greeting, self.name())
+        }
+    }
+}
+
+// public traits export their macro from the crate root, so one with the same name in another
+// module needs a macro of its own
+mod labels {
+    use enum_forward::forwardable;
+
+    #[forwardable]
+    pub trait GetName {
+        fn name(&self) -> String;
+    }
+}
+
+// identical to `labels::GetName`, so its exported macro can't be named after the trait alone
+mod tags {
+    use enum_forward::forwardable;
+
+    #[forwardable]
+    pub trait GetName {
+        fn name(&self) -> String;
+    }
+}
+
+use names::GetName;
+
+#[forwardable]
+trait Counter {
+    fn add(&mut self, amount: u32);
+    fn into_count(self) -> u32;
+}
+
+struct A {}
+struct B { count: u32 }
+struct Legacy { count: u32 }
+
+impl GetName for A {
+    fn name(&self) -> &'static str { "A" }
+}
+
+impl GetName for B {
+    fn name(&self) -> &'static str { "B" }
+    fn greet(&self, greeting: &str) -> String { format!("{} from B", greeting) }
+}
+
+impl GetName for Legacy {
+    fn name(&self) -> &'static str { "Legacy" }
+}
+
+impl Counter for B {
+    fn add(&mut self, amount: u32) { self.count += amount }
+    fn into_count(self) -> u32 { self.count }
+}
+
+impl Counter for Legacy {
+    fn add(&mut self, amount: u32) { self.count += amount }
+    fn into_count(self) -> u32 { self.count }
+}
+
+impl Display for A {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result { write!(f, "a") }
+}
+
+impl Display for B {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result { write!(f, "b{}", self.count) }
+}
+
+impl Display for Legacy {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result { write!(f, "legacy{}", self.count) }
+}
+
+fn legacy_name(_: &Legacy) -> &'static str { "Old" }
+
+fn legacy_add(legacy: &mut Legacy, amount: u32) { legacy.add(amount * 2) }
+
+#[derive(Forward)]
+#[forward(names::GetName, Display)]
+enum Foo {
+    A(A),
+    B { b: B },
+    #[forward_override(GetName::name = legacy_name)]
+    Legacy(Legacy),
+}
+
+#[derive(Forward)]
+#[forward(Counter)]
+enum Count {
+    B(B),
+    #[forward_override(Counter::add = legacy_add)]
+    Legacy(Legacy),
+}
+
+#[derive(Forward)]
+#[forward(Iterator, DoubleEndedIterator)]
+enum Iter<'a> {
+    Slice(std::slice::Iter<'a, u8>),
+    Rev(std::iter::Rev<std::slice::Iter<'a, u8>>),
+}
+
+#[derive(Forward)]
+#[forward(AsRef)]
+enum Text {
+    Owned(String),
+    Static(&'static str),
+}
+
+struct Label(&'static str);
+
+impl labels::GetName for Label {
+    fn name(&self) -> String { self.0.to_uppercase() }
+}
+
+#[derive(Forward)]
+#[forward(labels::GetName)]
+enum Tag {
+    Label(Label),
+}
+
+impl tags::GetName for Label {
+    fn name(&self) -> String { self.0.to_lowercase() }
+}
+
+#[derive(Forward)]
+#[forward(labels::GetName, tags::GetName)]
+enum Mark {
+    Label(Label),
+}
+
+#[test]
+fn forward_same_name() {
+    assert_eq!(labels::GetName::name(&Tag::Label(Label("tag"))), "TAG");
+    assert_eq!(labels::GetName::name(&Mark::Label(Label("Mark"))), "MARK");
+    assert_eq!(tags::GetName::name(&Mark::Label(Label("Mark"))), "mark");
+}
+
+#[test]
+fn forward_methods() {
+    assert_eq!(Foo::A(A {}).name(), "A");
+    assert_eq!(Foo::B { b: B { count: 0 } }.name(), "B");
+    assert_eq!(Foo::A(A {}).greet("Hello"), "Hello, A");
+    assert_eq!(Foo::B { b: B { count: 0 } }.greet("Hello"), "Hello from B");
+}
+
+#[test]
+fn forward_builtin() {
+    assert_eq!(Foo::A(A {}).to_string(), "a");
+    assert_eq!(Foo::B { b: B { count: 3 } }.to_string(), "b3");
+
+    let data = [1u8, 2, 3];
+    let forward = Iter::Slice(data.iter()).copied().collect::<Vec<_>>();
+    let reverse = Iter::Rev(data.iter().rev()).rev().copied().collect::<Vec<_>>();
+    assert_eq!(forward, vec![1, 2, 3]);
+    assert_eq!(reverse, vec![1, 2, 3]);
+
+    let owned = Text::Owned("owned".into());
+    let text: &str = owned.as_ref();
+    assert_eq!(text, "owned");
+    let bytes: &[u8] = Text::Static("static").as_ref();
+    assert_eq!(bytes, b"static");
+}
+
+#[test]
+fn forward_receivers() {
+    let mut count = Count::B(B { count: 1 });
+    count.add(2);
+    assert_eq!(count.into_count(), 3);
+}
+
+#[test]
+fn forward_override() {
+    let legacy = Foo::Legacy(Legacy { count: 1 });
+    assert_eq!(legacy.name(), "Old");
+    assert_eq!(legacy.greet("Hello"), "Hello, Legacy");
+    assert_eq!(legacy.to_string(), "legacy1");
+
+    let mut count = Count::Legacy(Legacy { count: 1 });
+    count.add(2);
+    assert_eq!(count.into_count(), 5);
+}
@@ -0,0 +1,98 @@
+use std::convert::TryInto;
+use enum_forward::{Forward, From, TryInto};
+
+struct Len {}
+
+impl<T, const N: usize> Forward<Len> for [T; N] {
+    type Output = usize;
+    fn forward(&self, _input: &Len) -> usize { N }
+}
+
+impl<T> Forward<Len> for &[T] {
+    type Output = usize;
+    fn forward(&self, _input: &Len) -> usize { self.len() }
+}
+
+impl Forward<Len> for String {
+    type Output = usize;
+    fn forward(&self, _input: &Len) -> usize { self.len() }
+}
+
+#[derive(From, TryInto, Forward)]
+enum Buf<'a, T, const N: usize> {
+    Arr([T; N]),
+    Slice(&'a [T]),
+}
+
+#[derive(Forward)]
+#[forward(AsRef)]
+enum Either<L, R> {
+    Left(L),
+    Right(R),
+}
+
+#[derive(TryInto, Forward)]
+#[forward(AsRef)]
+enum Wrap<T> where T: Clone, {
+    Val(T),
+    Name(String),
+}
+
+// variant types with the same names as the generated generic parameters
+struct I {}
+struct R {}
+
+impl Forward<Len> for I {
+    type Output = usize;
+    fn forward(&self, _input: &Len) -> usize { 1 }
+}
+
+impl Forward<Len> for R {
+    type Output = usize;
+    fn forward(&self, _input: &Len) -> usize { 2 }
+}
+
+#[derive(Forward)]
+enum Clash {
+    I(I),
+    R(R),
+}
+
+#[test]
+fn const_generics() {
+    let data = [1u8, 2, 3];
+    let arr: Buf<u8, 3> = data.into();
+    let slice: Buf<u8, 3> = data[..2].into();
+    assert_eq!(arr.forward(&Len {}), 3);
+    assert_eq!(slice.forward(&Len {}), 2);
+
+    let arr: [u8; 3] = arr.try_into().unwrap();
+    assert_eq!(arr, data);
+    let slice: Result<[u8; 3], _> = slice.try_into();
+    assert!(slice.is_err());
+}
+
+#[test]
+fn generic_variants() {
+    let left: Either<String, &str> = Either::Left("left".into());
+    let right: Either<String, &str> = Either::Right("right");
+    let left: &str = left.as_ref();
+    let right: &[u8] = right.as_ref();
+    assert_eq!(left, "left");
+    assert_eq!(right, b"right");
+
+    let val: Wrap<&[u8]> = Wrap::Val(b"val");
+    let name: Wrap<&[u8]> = Wrap::Name("name".into());
+    assert_eq!(val.forward(&Len {}), 3);
+    assert_eq!(name.forward(&Len {}), 4);
+    let bytes: &[u8] = name.as_ref();
+    assert_eq!(bytes, b"name");
+    let name: String = name.try_into().unwrap();
+    assert_eq!(name, "name");
+}
+
+#[test]
+fn generic_name_clash() {
+    assert_eq!(Clash::I(I {}).forward(&Len {}), 1);
+    assert_eq!(Clash::R(R {}).forward(&Len {}), 2);
+}
@@ -0,0 +1,79 @@
+use std::panic::Location;
+use enum_forward::{forwardable, Forward, ForwardEach, From};
+
+#[forwardable]
+trait Caller {
+    fn caller(&self) -> &'static Location<'static>;
+}
+
+struct A;
+struct B;
+
+impl Caller for A {
+    #[track_caller]
+    fn caller(&self) -> &'static Location<'static> { Location::caller() }
+}
+
+impl Caller for B {
+    #[track_caller]
+    fn caller(&self) -> &'static Location<'static> { Location::caller() }
+}
+
+#[derive(Forward, From)]
+#[forward(Caller)]
+#[enum_forward(inline = always, inline(caller = never), track_caller(caller))]
+enum Tracked {
+    A(A),
+    B(B),
+}
+
+#[derive(Forward, From)]
+#[forward(Caller)]
+#[enum_forward(inline = never, cold)]
+enum Untracked {
+    A(A),
+    B(B),
+}
+
+struct Where;
+
+impl enum_forward::Forward<Where> for A {
+    type Output = &'static Location<'static>;
+    #[track_caller]
+    fn forward(&self, _: &Where) -> Self::Output { Location::caller() }
+}
+
+impl enum_forward::Forward<Where> for B {
+    type Output = u32;
+    #[track_caller]
+    fn forward(&self, _: &Where) -> u32 { Location::caller().line() }
+}
+
+#[derive(ForwardEach)]
+#[enum_forward(track_caller(forward_each))]
+enum Each {
+    A(A),
+    B(B),
+}
+
+#[test]
+fn track_caller() {
+    let tracked = Tracked::from(B);
+    let line = line!(); let location = tracked.caller();
+    assert_eq!(location.file(), file!());
+    assert_eq!(location.line(), line);
+
+    let untracked = Untracked::from(A);
+    let line = line!(); let location = untracked.caller();
+    assert_ne!(location.line(), line);
+}
+
+#[test]
+fn track_caller_each() {
+    let line = line!(); let output = Each::A(A).forward_each(&Where);
+    let EachOutput::A(location) = output else { panic!("expected `A`") };
+    assert_eq!(location.line(), line);
+
+    let line = line!(); let output = Each::B(B).forward_each(&Where);
+    assert_eq!(output, EachOutput::B(line));
+}
@@ -0,0 +1,65 @@
+use enum_forward::Forward;
+
+#[derive(Forward)]
+#[forward(kind)]
+enum Literal {
+    Int(i64),
+    Float(f64),
+}
+
+#[derive(Forward)]
+#[forward(kind)]
+enum Operand {
+    #[forward(flatten)]
+    Literal(Literal),
+    Register(u8),
+}
+
+#[derive(Forward)]
+#[forward(kind)]
+enum Instruction {
+    #[forward(flatten)]
+    Operand(Operand),
+    Jump { target: usize },
+}
+
+#[test]
+fn kind() {
+    assert_eq!(Literal::Int(1).kind(), "Int");
+    assert_eq!(Operand::Register(0).kind(), "Register");
+    assert_eq!(Instruction::Jump { target: 4 }.kind(), "Jump");
+}
+
+#[test]
+fn kind_flattened() {
+    assert_eq!(Operand::Literal(Literal::Float(1.0)).kind(), "Float");
+    assert_eq!(Instruction::Operand(Operand::Register(3)).kind(), "Register");
+    assert_eq!(Instruction::Operand(Operand::Literal(Literal::Int(2))).kind(), "Int");
+}
+
+#[derive(Debug, PartialEq)]
+enum TokenKind {
+    Number,
+    Word,
+}
+
+#[derive(Forward)]
+enum Token {
+    Number(f64),
+    Word(String),
+}
+
+impl Token {
+    fn kind(&self) -> TokenKind {
+        match self {
+            Token::Number(_) => TokenKind::Number,
+            Token::Word(_) => TokenKind::Word,
+        }
+    }
+}
+
+#[test]
+fn own_kind() {
+    assert_eq!(Token::Number(1.5).kind(), TokenKind::Number);
+    assert_eq!(Token::Word("let".into()).kind(), TokenKind::Word);
+}
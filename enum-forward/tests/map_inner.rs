@@ -0,0 +1,77 @@
+use enum_forward::{Forward, MapInner, Mapper, TryMapInner, TryMapper};
+
+#[derive(Debug, PartialEq, Forward)]
+enum Tensor {
+    F32(Vec<f32>),
+    F64 { values: Vec<f64> },
+}
+
+trait Float: Copy + PartialOrd {
+    fn scale(self, by: f32) -> Self;
+    fn is_finite(self) -> bool;
+}
+
+impl Float for f32 {
+    fn scale(self, by: f32) -> Self { self * by }
+    fn is_finite(self) -> bool { f32::is_finite(self) }
+}
+
+impl Float for f64 {
+    fn scale(self, by: f32) -> Self { self * by as f64 }
+    fn is_finite(self) -> bool { f64::is_finite(self) }
+}
+
+struct Scale(f32);
+
+impl<T: Float> Mapper<Vec<T>> for Scale {
+    fn map(self, value: Vec<T>) -> Vec<T> {
+        value.into_iter().map(|x| x.scale(self.0)).collect()
+    }
+}
+
+struct Finite;
+
+impl<T: Float> TryMapper<Vec<T>> for Finite {
+    type Error = usize;
+
+    fn try_map(self, value: Vec<T>) -> Result<Vec<T>, usize> {
+        match value.iter().position(|x| !x.is_finite()) {
+            Some(index) => Err(index),
+            None => Ok(value),
+        }
+    }
+}
+
+#[test]
+fn map_keeps_variant() {
+    assert_eq!(Tensor::F32(vec![1.0, 2.0]).map_inner(Scale(2.0)), Tensor::F32(vec![2.0, 4.0]));
+    assert_eq!(Tensor::F64 { values: vec![0.5] }.map_inner(Scale(4.0)), Tensor::F64 { values: vec![2.0] });
+}
+
+#[test]
+fn try_map() {
+    assert_eq!(Tensor::F32(vec![1.0]).try_map_inner(Finite), Ok(Tensor::F32(vec![1.0])));
+    assert_eq!(Tensor::F64 { values: vec![1.0, f64::NAN] }.try_map_inner(Finite), Err(1));
+}
+
+#[derive(Debug, PartialEq, Forward)]
+enum Either<L, R> {
+    Left(L),
+    Right(R),
+}
+
+struct Double;
+
+impl Mapper<i32> for Double {
+    fn map(self, value: i32) -> i32 { value * 2 }
+}
+
+impl Mapper<String> for Double {
+    fn map(self, value: String) -> String { value.repeat(2) }
+}
+
+#[test]
+fn generic() {
+    assert_eq!(Either::<i32, String>::Left(3).map_inner(Double), Either::Left(6));
+    assert_eq!(Either::<i32, String>::Right("ab".into()).map_inner(Double), Either::Right("abab".to_string()));
+}
@@ -0,0 +1,211 @@
+use std::ops::{Add, AddAssign, Mul, MulAssign, Neg};
+use enum_forward::{Forward, VariantMismatch};
+
+#[derive(Debug, Clone, PartialEq)]
+struct Tensor<T>(Vec<T>);
+
+impl<T: Neg<Output = T>> Neg for Tensor<T> {
+    type Output = Self;
+    fn neg(self) -> Self { Tensor(self.0.into_iter().map(|x| -x).collect()) }
+}
+
+impl<T: Add<Output = T>> Add for Tensor<T> {
+    type Output = Self;
+    fn add(self, rhs: Self) -> Self { Tensor(self.0.into_iter().zip(rhs.0).map(|(a, b)| a + b).collect()) }
+}
+
+impl<T: AddAssign> AddAssign for Tensor<T> {
+    fn add_assign(&mut self, rhs: Self) {
+        self.0.iter_mut().zip(rhs.0).for_each(|(a, b)| *a += b);
+    }
+}
+
+impl Mul<f32> for Tensor<f32> {
+    type Output = Self;
+    fn mul(self, rhs: f32) -> Self { Tensor(self.0.into_iter().map(|x| x * rhs).collect()) }
+}
+
+impl Mul<f32> for Tensor<i32> {
+    type Output = Self;
+    fn mul(self, rhs: f32) -> Self { Tensor(self.0.into_iter().map(|x| (x as f32 * rhs) as i32).collect()) }
+}
+
+impl MulAssign<f32> for Tensor<f32> {
+    fn mul_assign(&mut self, rhs: f32) {
+        self.0.iter_mut().for_each(|x| *x *= rhs);
+    }
+}
+
+impl MulAssign<f32> for Tensor<i32> {
+    fn mul_assign(&mut self, rhs: f32) {
+        self.0.iter_mut().for_each(|x| *x = (*x as f32 * rhs) as i32);
+    }
+}
+
+impl<T> std::ops::Index<usize> for Tensor<T> {
+    type Output = T;
+    fn index(&self, index: usize) -> &T { &self.0[index] }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Double(f64);
+
+impl Mul<f32> for Double {
+    type Output = Self;
+    fn mul(self, rhs: f32) -> Self { Double(self.0 * rhs as f64) }
+}
+
+impl Neg for Double {
+    type Output = Self;
+    fn neg(self) -> Self { Double(-self.0) }
+}
+
+#[derive(Debug, Clone, PartialEq, Forward)]
+#[forward(Neg, Add, AddAssign, Mul<f32>, MulAssign<f32>)]
+enum AnyTensor {
+    F32(Tensor<f32>),
+    I32 { tensor: Tensor<i32> },
+}
+
+#[derive(Debug, Clone, PartialEq, Forward)]
+#[forward(Add)]
+#[forward_ops(mismatch = error)]
+enum Checked {
+    F32(Tensor<f32>),
+    I32(Tensor<i32>),
+}
+
+#[derive(Debug, PartialEq, Forward)]
+#[forward(core::ops::Neg, ops::Mul<f32>)]
+enum Generic<T> {
+    Double(Double),
+    Other(T),
+}
+
+#[derive(Forward)]
+#[forward(Index)]
+enum Floats {
+    Tensor(Tensor<f32>),
+    Vec(Vec<f32>),
+}
+
+#[test]
+fn unary() {
+    assert_eq!(-AnyTensor::F32(Tensor(vec![1.0, -2.0])), AnyTensor::F32(Tensor(vec![-1.0, 2.0])));
+    assert_eq!(-AnyTensor::I32 { tensor: Tensor(vec![3]) }, AnyTensor::I32 { tensor: Tensor(vec![-3]) });
+    assert_eq!(-Generic::<Double>::Double(Double(1.0)), Generic::Double(Double(-1.0)));
+}
+
+#[test]
+fn scalar() {
+    assert_eq!(AnyTensor::F32(Tensor(vec![1.0, 2.0])) * 2.0, AnyTensor::F32(Tensor(vec![2.0, 4.0])));
+    let mut tensor = AnyTensor::I32 { tensor: Tensor(vec![2, 4]) };
+    tensor *= 0.5;
+    assert_eq!(tensor, AnyTensor::I32 { tensor: Tensor(vec![1, 2]) });
+    assert_eq!(Generic::<Double>::Other(Double(2.0)) * 3.0, Generic::Other(Double(6.0)));
+}
+
+#[test]
+fn same_variant() {
+    let a = AnyTensor::F32(Tensor(vec![1.0, 2.0]));
+    assert_eq!(a.clone() + a.clone(), AnyTensor::F32(Tensor(vec![2.0, 4.0])));
+    let mut b = a.clone();
+    b += a;
+    assert_eq!(b, AnyTensor::F32(Tensor(vec![2.0, 4.0])));
+}
+
+#[test]
+#[should_panic(expected = "mismatched variants `AnyTensor::F32` and `AnyTensor::I32`")]
+fn mismatch_panics() {
+    let _ = AnyTensor::F32(Tensor(vec![1.0])) + AnyTensor::I32 { tensor: Tensor(vec![1]) };
+}
+
+#[test]
+fn mismatch_error() {
+    let sum = Checked::F32(Tensor(vec![1.0])) + Checked::F32(Tensor(vec![2.0]));
+    assert_eq!(sum, Ok(Checked::F32(Tensor(vec![3.0]))));
+    let sum = Checked::I32(Tensor(vec![1])) + Checked::F32(Tensor(vec![2.0]));
+    assert_eq!(sum, Err(VariantMismatch { left: "Checked::I32", right: "Checked::F32" }));
+}
+
+#[test]
+fn index() {
+    assert_eq!(Floats::Tensor(Tensor(vec![1.0, 2.0]))[1], 2.0);
+    assert_eq!(Floats::Vec(vec![3.0])[0], 3.0);
+}
+
+mod counting {
+    use enum_forward::forwardable;
+
+    // shares its name with `core::ops::Add`, which a bare `Add` no longer means when it's in scope
+    #[forwardable]
+    pub trait Add {
+        fn add(&self, amount: u32) -> u32;
+    }
+
+    pub struct Counter(pub u32);
+
+    impl Add for Counter {
+        fn add(&self, amount: u32) -> u32 { self.0 + amount }
+    }
+
+    impl std::ops::Neg for Counter {
+        type Output = Counter;
+        fn neg(self) -> Counter { Counter(u32::MAX - self.0) }
+    }
+}
+
+mod shadowed {
+    use enum_forward::Forward;
+    use super::counting::{Add, Counter};
+
+    #[derive(Forward)]
+    #[forward(Add, Neg)]
+    pub enum Tally {
+        Counter(Counter),
+    }
+}
+
+#[test]
+fn forwardable_shadows_builtin() {
+    use counting::{Add, Counter};
+    use shadowed::Tally;
+
+    assert_eq!(Tally::Counter(Counter(1)).add(2), 3);
+    let Tally::Counter(negated) = -Tally::Counter(Counter(1));
+    assert_eq!(negated.0, u32::MAX - 1);
+}
+
+mod macros {
+    macro_rules! index_of {
+        ($haystack:expr, $needle:expr) => { $haystack.iter().position(|x| *x == $needle) };
+    }
+    pub(crate) use index_of as Index;
+}
+
+mod globbed {
+    use enum_forward::Forward;
+    use super::macros::*;
+    use super::counting::Counter;
+
+    // the fallback for `Neg` is the only name imported beside the item, so `Index!` isn't ambiguous
+    #[derive(Forward)]
+    #[forward(Neg)]
+    pub enum Tally {
+        Counter(Counter),
+    }
+
+    pub fn position(values: &[u32], value: u32) -> Option<usize> {
+        Index!(values, value)
+    }
+}
+
+#[test]
+fn builtin_fallback_stays_narrow() {
+    use counting::Counter;
+    use globbed::{position, Tally};
+
+    let Tally::Counter(negated) = -Tally::Counter(Counter(0));
+    assert_eq!(negated.0, u32::MAX);
+    assert_eq!(position(&[3, 5, 8], 5), Some(1));
+}
@@ -0,0 +1,27 @@
+use enum_forward::{From, TryInto};
+
+mod shapes {
+    #[derive(Debug, PartialEq)]
+    pub struct Circle(pub f32);
+
+    #[derive(Debug, PartialEq)]
+    pub struct Square(pub f32);
+}
+
+pub use shapes::{Circle, Square};
+
+#[derive(Debug, PartialEq, From, TryInto)]
+#[from(register)]
+pub enum Shape {
+    Circle(Circle),
+    Square { square: Square },
+    #[enum_forward(skip)]
+    Empty,
+}
+
+#[derive(Debug, PartialEq, From, TryInto)]
+#[from(register)]
+pub enum Generic<A, B> {
+    Vec(Vec<A>),
+    Option(Option<B>),
+}
@@ -0,0 +1,59 @@
+#![cfg(feature = "serde")]
+
+use std::collections::BTreeMap;
+use enum_forward::{ForwardDeserialize, ForwardSerialize};
+
+#[derive(Debug, PartialEq, ForwardSerialize, ForwardDeserialize)]
+enum Value {
+    Number(u64),
+    Text(String),
+    List { items: Vec<Value> },
+    Map(BTreeMap<String, Value>),
+}
+
+#[derive(Debug, PartialEq, ForwardSerialize, ForwardDeserialize)]
+enum Pair<T> {
+    One(T),
+    Two((T, T)),
+}
+
+#[derive(ForwardSerialize)]
+struct Wrapper {
+    #[forward]
+    value: Value,
+    #[allow(dead_code)]
+    cached: bool,
+}
+
+#[test]
+fn serialize_inner() {
+    let value = Value::List { items: vec![Value::Number(1), Value::Text("two".into())] };
+    assert_eq!(serde_json::to_string(&value).unwrap(), r#"[1,"two"]"#);
+    let wrapper = Wrapper { value, cached: true };
+    assert_eq!(serde_json::to_string(&wrapper).unwrap(), r#"[1,"two"]"#);
+    assert_eq!(serde_json::to_string(&Pair::Two((1, 2))).unwrap(), "[1,2]");
+}
+
+#[test]
+fn deserialize_in_order() {
+    let value: Value = serde_json::from_str(r#"{"a": [1, "b"], "c": 3}"#).unwrap();
+    let expected = BTreeMap::from([
+        ("a".to_string(), Value::List { items: vec![Value::Number(1), Value::Text("b".into())] }),
+        ("c".to_string(), Value::Number(3)),
+    ]);
+    assert_eq!(value, Value::Map(expected));
+
+    let one: Pair<u8> = serde_json::from_str("4").unwrap();
+    let two: Pair<u8> = serde_json::from_str("[4, 5]").unwrap();
+    assert_eq!(one, Pair::One(4));
+    assert_eq!(two, Pair::Two((4, 5)));
+}
+
+#[test]
+fn deserialize_errors() {
+    let err = serde_json::from_str::<Value>("true").unwrap_err().to_string();
+    assert!(err.starts_with("data did not match any variant of `Value`"), "{}", err);
+    for attempted in ["Number (u64)", "Text (String)", "List (Vec<Value>)", "Map (BTreeMap<String, Value>)"] {
+        assert!(err.contains(attempted), "{} is missing {}", err, attempted);
+    }
+}
@@ -0,0 +1,41 @@
+#![cfg(feature = "alloc")]
+
+use std::rc::Rc;
+use std::sync::Arc;
+use enum_forward::Forward;
+
+struct Name {}
+
+struct A {}
+struct B {}
+
+impl Forward<Name> for A {
+    type Output = &'static str;
+    fn forward(&self, _input: &Name) -> &'static str { "A" }
+}
+
+impl Forward<Name> for B {
+    type Output = &'static str;
+    fn forward(&self, _input: &Name) -> &'static str { "B" }
+}
+
+#[derive(Forward)]
+enum Foo {
+    A(Box<A>),
+    B(Rc<B>),
+    Dyn(Arc<dyn Forward<Name, Output=&'static str>>),
+}
+
+#[test]
+fn forward_pointers() {
+    assert_eq!(Box::new(A {}).forward(&Name {}), "A");
+    assert_eq!(Rc::new(B {}).forward(&Name {}), "B");
+    assert_eq!(Arc::new(A {}).forward(&Name {}), "A");
+}
+
+#[test]
+fn forward_pointer_variants() {
+    assert_eq!(Foo::A(Box::new(A {})).forward(&Name {}), "A");
+    assert_eq!(Foo::B(Rc::new(B {})).forward(&Name {}), "B");
+    assert_eq!(Foo::Dyn(Arc::new(B {})).forward(&Name {}), "B");
+}
@@ -0,0 +1,172 @@
+use enum_forward::{From, StateMachine, Step};
+
+#[derive(Debug, PartialEq)]
+struct Idle;
+
+#[derive(Debug, PartialEq)]
+struct Handshake { attempts: u32 }
+
+#[derive(Debug, PartialEq)]
+struct Open { received: Vec<u8> }
+
+#[derive(Debug, PartialEq)]
+enum Event {
+    Connect,
+    Ack,
+    Data(u8),
+    Close,
+}
+
+impl Step<Event> for Idle {
+    type Next = Conn;
+
+    fn step(self, event: Event) -> Conn {
+        match event {
+            Event::Connect => Handshake { attempts: 1 }.into(),
+            _ => self.into(),
+        }
+    }
+}
+
+impl Step<Event> for Handshake {
+    type Next = Conn;
+
+    fn step(self, event: Event) -> Conn {
+        match event {
+            Event::Ack => Open { received: vec![] }.into(),
+            Event::Close => Idle.into(),
+            _ => Handshake { attempts: self.attempts + 1 }.into(),
+        }
+    }
+}
+
+impl Step<Event> for Open {
+    type Next = Conn;
+
+    fn step(mut self, event: Event) -> Conn {
+        match event {
+            Event::Data(byte) => {
+                self.received.push(byte);
+                self.into()
+            }
+            Event::Close => Idle.into(),
+            _ => self.into(),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, From, StateMachine)]
+enum Conn {
+    Idle(Idle),
+    Handshake(Handshake),
+    Open { open: Open },
+}
+
+#[test]
+fn step() {
+    let conn = Conn::Idle(Idle);
+    let conn = conn.step(Event::Connect);
+    assert_eq!(conn, Conn::Handshake(Handshake { attempts: 1 }));
+    let conn = conn.step(Event::Data(0)).step(Event::Ack).step(Event::Data(7));
+    assert_eq!(conn, Conn::Open { open: Open { received: vec![7] } });
+    assert_eq!(conn.step(Event::Close), Conn::Idle(Idle));
+}
+
+mod checked {
+    use enum_forward::{From, StateMachine, Step};
+
+    #[derive(Debug, PartialEq)]
+    pub struct Locked;
+
+    #[derive(Debug, PartialEq)]
+    pub struct Unlocked;
+
+    #[derive(Debug, PartialEq)]
+    pub struct Broken;
+
+    pub struct Push;
+    pub struct Coin(pub bool);
+
+    impl Step<Coin> for Locked {
+        type Next = Turnstile;
+
+        fn step(self, coin: Coin) -> Turnstile {
+            if coin.0 { Unlocked.into() } else { Broken.into() }
+        }
+    }
+
+    impl Step<Push> for Locked {
+        type Next = Locked;
+
+        fn step(self, _: Push) -> Locked { self }
+    }
+
+    impl Step<Coin> for Unlocked {
+        type Next = Unlocked;
+
+        fn step(self, _: Coin) -> Unlocked { self }
+    }
+
+    impl Step<Push> for Unlocked {
+        type Next = Locked;
+
+        fn step(self, _: Push) -> Locked { Locked }
+    }
+
+    impl<E> Step<E> for Broken {
+        type Next = Broken;
+
+        fn step(self, _: E) -> Broken { self }
+    }
+
+    #[derive(Debug, PartialEq, From, StateMachine)]
+    #[state_machine(transitions(Locked => Unlocked, Unlocked => Locked))]
+    pub enum Turnstile {
+        Locked(Locked),
+        Unlocked(Unlocked),
+        Broken(Broken),
+    }
+
+    pub struct Kick;
+
+    impl Step<Kick> for Locked {
+        type Next = Broken;
+
+        fn step(self, _: Kick) -> Broken { Broken }
+    }
+
+    // shares its states with `Turnstile`, but a locked gate is allowed to break
+    #[derive(Debug, PartialEq, From, StateMachine)]
+    #[state_machine(transitions(Locked => Broken))]
+    pub enum Gate {
+        Locked(Locked),
+        Broken(Broken),
+    }
+}
+
+#[test]
+fn allowed_transitions() {
+    use checked::*;
+
+    let turnstile = Turnstile::Locked(Locked).step(Coin(true));
+    assert_eq!(turnstile, Turnstile::Unlocked(Unlocked));
+    let turnstile = turnstile.step(Coin(true)).step(Push).step(Push);
+    assert_eq!(turnstile, Turnstile::Locked(Locked));
+}
+
+#[test]
+fn shared_states() {
+    use checked::*;
+
+    assert_eq!(Gate::Locked(Locked).step(Kick), Gate::Broken(Broken));
+    assert_eq!(Gate::Broken(Broken).step(Push), Gate::Broken(Broken));
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "`Turnstile::Locked` is not allowed to move to `Turnstile::Broken`")]
+fn disallowed_transition() {
+    use checked::*;
+
+    Turnstile::Locked(Locked).step(Coin(false));
+}
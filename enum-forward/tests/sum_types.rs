@@ -0,0 +1,55 @@
+use enum_forward::Forward;
+
+struct Name {}
+
+struct A {}
+struct B {}
+
+impl Forward<Name> for A {
+    type Output = &'static str;
+    fn forward(&self, _input: &Name) -> &'static str { "A" }
+}
+
+impl Forward<Name> for B {
+    type Output = &'static str;
+    fn forward(&self, _input: &Name) -> &'static str { "B" }
+}
+
+// generic code that only knows about `Forward`
+fn name<T: Forward<Name>>(value: &T) -> T::Output {
+    value.forward(&Name {})
+}
+
+#[derive(Forward)]
+enum Foo {
+    A(Option<A>),
+    B(Option<B>),
+}
+
+#[test]
+fn forward_option() {
+    assert_eq!(name(&Some(A {})), Some("A"));
+    assert_eq!(name(&None::<A>), None);
+}
+
+#[test]
+fn forward_result() {
+    assert_eq!(name(&Ok::<A, B>(A {})), "A");
+    assert_eq!(name(&Err::<A, B>(B {})), "B");
+}
+
+#[test]
+fn forward_nested() {
+    assert_eq!(name(&Some(Ok::<A, B>(A {}))), Some("A"));
+    assert_eq!(name(&Foo::A(Some(A {}))), Some("A"));
+    assert_eq!(name(&Foo::B(None)), None);
+}
+
+#[cfg(feature = "either")]
+#[test]
+fn forward_either() {
+    use either::Either;
+
+    assert_eq!(name(&Either::<A, B>::Left(A {})), "A");
+    assert_eq!(name(&Either::<A, B>::Right(B {})), "B");
+}
@@ -0,0 +1,141 @@
+use enum_forward::{Forward, Mapper};
+
+#[derive(Debug, Default, PartialEq)]
+struct Idle;
+
+#[derive(Debug, PartialEq)]
+struct Handshake { attempts: u32 }
+
+#[derive(Debug, PartialEq)]
+struct Open { buffer: Vec<u8> }
+
+#[derive(Debug, PartialEq, Forward)]
+enum Conn {
+    #[default_variant]
+    Idle(Idle),
+    Handshake(Handshake),
+    Open { open: Open },
+}
+
+#[test]
+fn take() {
+    let mut conn = Conn::Open { open: Open { buffer: vec![1, 2] } };
+    assert_eq!(conn.take(), Conn::Open { open: Open { buffer: vec![1, 2] } });
+    assert_eq!(conn, Conn::Idle(Idle));
+    assert_eq!(conn.take(), Conn::Idle(Idle));
+}
+
+struct Retry;
+
+impl Mapper<Idle> for Retry {
+    fn map(self, value: Idle) -> Idle { value }
+}
+
+impl Mapper<Handshake> for Retry {
+    fn map(self, value: Handshake) -> Handshake { Handshake { attempts: value.attempts + 1 } }
+}
+
+impl Mapper<Open> for Retry {
+    fn map(self, mut value: Open) -> Open {
+        value.buffer.clear();
+        value
+    }
+}
+
+#[test]
+fn replace_with() {
+    let mut conn = Conn::Handshake(Handshake { attempts: 1 });
+    conn.replace_with(Retry);
+    assert_eq!(conn, Conn::Handshake(Handshake { attempts: 2 }));
+
+    let mut conn = Conn::Open { open: Open { buffer: vec![1] } };
+    conn.replace_with(Retry);
+    assert_eq!(conn, Conn::Open { open: Open { buffer: vec![] } });
+}
+
+#[derive(Debug, PartialEq, Forward)]
+enum Slot<T> {
+    Value(T),
+    #[default_variant]
+    Empty(Option<T>),
+}
+
+#[test]
+fn generic() {
+    let mut slot = Slot::Value(3);
+    assert_eq!(slot.take(), Slot::Value(3));
+    assert_eq!(slot, Slot::Empty(None));
+}
+
+#[derive(Debug, PartialEq, Forward)]
+#[forward(replace_inner)]
+enum Reading {
+    #[default_variant]
+    Raw(u32),
+    Filtered(u32),
+}
+
+#[test]
+fn replace_with_closure() {
+    let mut reading = Reading::Filtered(4);
+    reading.replace_with(|value: u32| value * 2);
+    assert_eq!(reading, Reading::Filtered(8));
+
+    let mut reading = Reading::Raw(1);
+    let offset = 10;
+    reading.replace_with(move |value| value + offset);
+    assert_eq!(reading, Reading::Raw(11));
+}
+
+#[test]
+fn replace_inner() {
+    let mut reading = Reading::Filtered(4);
+    assert_eq!(reading.replace_inner(5), 4);
+    assert_eq!(reading, Reading::Filtered(5));
+    assert_eq!(reading.replace_inner(0), 5);
+    assert_eq!(reading, Reading::Filtered(0));
+}
+
+#[test]
+fn replace_with_panic() {
+    let mut reading = Reading::Filtered(4);
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        reading.replace_with(|_: u32| -> u32 { panic!("sensor failed") });
+    }));
+    assert!(result.is_err());
+    assert_eq!(reading, Reading::Raw(0));
+}
+
+#[derive(Debug, PartialEq, Forward)]
+enum Sample {
+    Left(i16),
+    Right(i16),
+}
+
+impl Sample {
+    fn replace_inner(&mut self, inner: i16) -> Sample {
+        let old = match self {
+            Sample::Left(value) => Sample::Left(*value),
+            Sample::Right(value) => Sample::Right(*value),
+        };
+        *self = Sample::Left(inner);
+        old
+    }
+}
+
+#[test]
+fn own_replace_inner() {
+    let mut sample = Sample::Right(3);
+    assert_eq!(sample.replace_inner(7), Sample::Right(3));
+    assert_eq!(sample, Sample::Left(7));
+}
+
+#[test]
+fn map_with_closure() {
+    use enum_forward::{MapInner, TryMapInner};
+
+    assert_eq!(Reading::Raw(3).map_inner(|value: u32| value + 1), Reading::Raw(4));
+    let checked = |value: u32| value.checked_sub(5).ok_or("underflow");
+    assert_eq!(Reading::Filtered(7).try_map_inner(checked), Ok(Reading::Filtered(2)));
+    assert_eq!(Reading::Filtered(3).try_map_inner(checked), Err("underflow"));
+}
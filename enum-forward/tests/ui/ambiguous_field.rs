@@ -0,0 +1,14 @@
+use enum_forward::Forward;
+
+#[derive(Forward)]
+struct Pair(u32, u32);
+
+#[derive(Forward)]
+struct Marked {
+    #[forward]
+    a: u32,
+    #[forward]
+    b: u32,
+}
+
+fn main() {}
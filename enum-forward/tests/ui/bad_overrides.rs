@@ -0,0 +1,23 @@
+use enum_forward::{forwardable, Forward};
+
+#[forwardable]
+trait GetName {
+    fn name(&self) -> String;
+}
+
+impl GetName for u8 {
+    fn name(&self) -> String { "u8".into() }
+}
+
+fn other_name(_: &u8) -> String { "other".into() }
+
+#[derive(Forward)]
+#[forward(GetName)]
+enum Foo {
+    #[forward_override(Display::fmt = other_name)]
+    A(u8),
+    #[forward_override(GetName::title = other_name)]
+    B(u8),
+}
+
+fn main() {}
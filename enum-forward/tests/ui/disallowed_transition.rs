@@ -0,0 +1,37 @@
+use enum_forward::{From, StateMachine, Step};
+
+struct Locked;
+struct Unlocked;
+struct Broken;
+
+struct Coin;
+
+impl Step<Coin> for Locked {
+    type Next = Broken;
+
+    fn step(self, _: Coin) -> Broken { Broken }
+}
+
+impl Step<Coin> for Unlocked {
+    type Next = Locked;
+
+    fn step(self, _: Coin) -> Locked { Locked }
+}
+
+impl Step<Coin> for Broken {
+    type Next = Broken;
+
+    fn step(self, _: Coin) -> Broken { self }
+}
+
+#[derive(From, StateMachine)]
+#[state_machine(transitions(Locked => Unlocked, Unlocked => Locked | Jammed))]
+enum Turnstile {
+    Locked(Locked),
+    Unlocked(Unlocked),
+    Broken(Broken),
+}
+
+fn main() {
+    let _ = Turnstile::Locked(Locked).step(Coin);
+}
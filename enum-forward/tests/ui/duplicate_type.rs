@@ -0,0 +1,12 @@
+use enum_forward::From;
+
+// equivalent paths are the same type
+#[derive(From)]
+enum Foo {
+    Text(String),
+    Name(std::string::String),
+    Bytes(Vec<u8>),
+    Data(::std::vec::Vec::<u8>),
+}
+
+fn main() {}
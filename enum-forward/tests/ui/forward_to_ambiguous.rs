@@ -0,0 +1,21 @@
+use enum_forward::{forward_to, Forward};
+
+trait GetName {
+    fn name(&self) -> &'static str;
+}
+
+struct A {}
+
+impl GetName for A {
+    fn name(&self) -> &'static str { "A" }
+}
+
+#[derive(Forward)]
+enum Foo {
+    A(A),
+}
+
+#[forward_to(_: &Foo as GetName)]
+fn name(_: &Foo, _: &Foo) -> &'static str;
+
+fn main() {}
\ No newline at end of file
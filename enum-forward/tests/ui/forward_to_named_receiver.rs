@@ -0,0 +1,23 @@
+use enum_forward::{forward_to, Forward};
+
+trait GetName {
+    fn name(&self) -> &'static str;
+}
+
+struct A {}
+
+impl GetName for A {
+    fn name(&self) -> &'static str { "A" }
+}
+
+#[derive(Forward)]
+enum Foo {
+    A(A),
+}
+
+impl Foo {
+    #[forward_to(foo: Foo as GetName)]
+    fn name(&self) -> &'static str;
+}
+
+fn main() {}
\ No newline at end of file
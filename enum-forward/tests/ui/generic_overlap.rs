@@ -0,0 +1,10 @@
+use enum_forward::{From, TryInto};
+
+#[derive(From, TryInto)]
+enum Foo<T> {
+    Byte(u8),
+    Generic(Option<T>),
+    Other(Option<u8>),
+}
+
+fn main() {}
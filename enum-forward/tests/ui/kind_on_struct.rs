@@ -0,0 +1,10 @@
+use enum_forward::Forward;
+
+#[derive(Forward)]
+#[forward(kind)]
+struct Wrapper {
+    #[forward]
+    value: u32,
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+use enum_forward::Forward;
+
+#[derive(Forward)]
+enum Conn {
+    #[default_variant]
+    Idle(u8),
+    #[default_variant]
+    Open(u16),
+}
+
+fn main() {}
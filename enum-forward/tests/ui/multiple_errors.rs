@@ -0,0 +1,18 @@
+use enum_forward::{Forward, From, TryInto};
+
+// every bad variant is reported at once
+#[derive(From, TryInto, Forward)]
+enum Foo {
+    Unit,
+    Pair(u8, u16),
+    Text(String),
+    Other(String),
+    Named { a: u32, b: u32 },
+    Number(u32),
+}
+
+fn main() {
+    // the valid impls are still generated
+    let _: Foo = 3u32.into();
+    let _: Foo = String::new().into();
+}
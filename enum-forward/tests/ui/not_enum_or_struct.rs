@@ -0,0 +1,9 @@
+use enum_forward::Forward;
+
+#[derive(Forward)]
+union Foo {
+    a: u32,
+    b: f32,
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+use enum_forward::Forward;
+
+#[derive(Forward)]
+#[forward(replace_inner)]
+enum Reading {
+    Raw(u32),
+    Scaled(f32),
+}
+
+fn main() {}
@@ -0,0 +1,17 @@
+use enum_forward::{forwardable, Forward};
+
+#[forwardable]
+trait Make {
+    const ID: u32;
+    type Item<'a>;
+    fn make() -> Self;
+    fn boxed(self: Box<Self>) -> u32;
+}
+
+#[derive(Forward)]
+#[forward(Make)]
+enum Foo {
+    A(u8),
+}
+
+fn main() {}
@@ -0,0 +1,37 @@
+use enum_forward::{forwardable, Forward, ForwardPartialEq, From};
+
+#[forwardable(krate = enum_forward)]
+trait GetName {
+    fn name(&self) -> String;
+}
+
+#[derive(From)]
+enum Foo {
+    #[enum_forward(skipped)]
+    A(u8),
+    B(u16),
+}
+
+#[derive(Forward)]
+#[forward(Any)]
+enum Bar {
+    #[forward(flat)]
+    A(u8),
+    B(u16),
+}
+
+#[derive(ForwardPartialEq)]
+#[forward_cmp(order = size)]
+enum Baz {
+    A(u8),
+    B(u16),
+}
+
+#[derive(Forward)]
+#[enum_forward(inline = sometimes, cold(fmt))]
+enum Qux {
+    A(u8),
+    B(u16),
+}
+
+fn main() {}
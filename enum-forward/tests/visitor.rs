@@ -0,0 +1,151 @@
+use std::any::Any;
+use enum_forward::ForwardVisitor;
+
+struct Literal(i64);
+struct Negate(Box<Expr>);
+struct Sum { terms: Vec<Expr> }
+struct Variable<'a> { name: &'a str }
+
+#[derive(ForwardVisitor)]
+enum Expr {
+    Literal(Literal),
+    Negate(Negate),
+    Sum(Sum),
+}
+
+#[derive(ForwardVisitor)]
+enum Term<'a> {
+    Expr(Expr),
+    Variable { variable: Variable<'a> },
+}
+
+#[derive(Default)]
+struct Evaluate {
+    stack: Vec<i64>,
+}
+
+impl ExprVisitor for Evaluate {
+    fn visit_literal(&mut self, value: &Literal) {
+        self.stack.push(value.0);
+    }
+
+    fn visit_negate(&mut self, value: &Negate) {
+        value.0.accept(self);
+        let top = self.stack.pop().unwrap();
+        self.stack.push(-top);
+    }
+
+    fn visit_sum(&mut self, value: &Sum) {
+        let mut sum = 0;
+        for term in &value.terms {
+            term.accept(self);
+            sum += self.stack.pop().unwrap();
+        }
+        self.stack.push(sum);
+    }
+}
+
+fn expr() -> Expr {
+    Expr::Sum(Sum { terms: vec![
+        Expr::Literal(Literal(5)),
+        Expr::Negate(Negate(Box::new(Expr::Literal(Literal(2))))),
+    ] })
+}
+
+#[test]
+fn accept() {
+    let mut evaluate = Evaluate::default();
+    expr().accept(&mut evaluate);
+    assert_eq!(evaluate.stack, [3]);
+}
+
+struct Double;
+
+impl ExprVisitor for Double {
+    fn visit_literal_mut(&mut self, value: &mut Literal) {
+        value.0 *= 2;
+    }
+
+    fn visit_negate_mut(&mut self, value: &mut Negate) {
+        value.0.accept_mut(self);
+    }
+
+    fn visit_sum_mut(&mut self, value: &mut Sum) {
+        for term in &mut value.terms {
+            term.accept_mut(self);
+        }
+    }
+}
+
+#[test]
+fn accept_mut() {
+    let mut expr = expr();
+    expr.accept_mut(&mut Double);
+    let mut evaluate = Evaluate::default();
+    expr.accept(&mut evaluate);
+    assert_eq!(evaluate.stack, [6]);
+}
+
+#[derive(Default)]
+struct Count {
+    exprs: usize,
+    variables: Vec<String>,
+}
+
+impl<'a> TermVisitor<'a> for Count {
+    fn visit_any(&mut self, value: &dyn Any) {
+        if value.is::<Expr>() {
+            self.exprs += 1;
+        }
+    }
+
+    fn visit_variable(&mut self, value: &Variable<'a>) {
+        self.variables.push(value.name.to_string());
+    }
+}
+
+#[test]
+fn accept_dyn() {
+    let mut count = Count::default();
+    let visitor: &mut dyn TermVisitor = &mut count;
+    let terms = [Term::Expr(expr()), Term::Variable { variable: Variable { name: "x" } }, Term::Expr(expr())];
+    for term in &terms {
+        term.accept(visitor);
+    }
+    assert_eq!(count.exprs, 2);
+    assert_eq!(count.variables, ["x"]);
+}
+
+struct Ignore;
+
+impl ExprVisitor for Ignore {}
+
+#[test]
+fn default_to_nothing() {
+    let mut expr = expr();
+    expr.accept(&mut Ignore);
+    expr.accept_mut(&mut Ignore);
+
+    // `Count` only visits shared values
+    let mut count = Count::default();
+    Term::Expr(expr).accept_mut(&mut count);
+    assert_eq!(count.exprs, 0);
+}
+
+#[derive(Default)]
+struct Seen(usize);
+
+impl<'a> TermVisitor<'a> for Seen {
+    fn visit_any(&mut self, _value: &dyn Any) {
+        self.0 += 1;
+    }
+}
+
+#[test]
+fn borrowed_skip_visit_any() {
+    let mut seen = Seen::default();
+    Term::Expr(expr()).accept(&mut seen);
+    // `Variable<'a>` may borrow, so it can't be passed as `&dyn Any`
+    Term::Variable { variable: Variable { name: "x" } }.accept(&mut seen);
+    assert_eq!(seen.0, 1);
+}